@@ -0,0 +1,81 @@
+//! Issuing many tokens with the same key.
+
+use ed25519_dalek::{
+    hazmat::{raw_sign, ExpandedSecretKey},
+    Sha512, SigningKey, VerifyingKey,
+};
+use serde::Serialize;
+
+use crate::{signing_preimage, Expires, Rcan};
+
+/// Issues tokens in bulk for one issuer.
+///
+/// Signing with a [`SigningKey`] expands the secret key for every
+/// signature. A `BatchIssuer` expands it once and reuses it, and is
+/// otherwise the same as signing each token with
+/// [`Rcan::issuing_builder`].
+pub struct BatchIssuer<'s> {
+    issuer: &'s SigningKey,
+    expanded: ExpandedSecretKey,
+}
+
+impl<'s> BatchIssuer<'s> {
+    /// Creates an issuer signing with `issuer`.
+    pub fn new(issuer: &'s SigningKey) -> Self {
+        Self {
+            issuer,
+            expanded: ExpandedSecretKey::from(issuer.as_bytes()),
+        }
+    }
+
+    /// The key tokens are issued by.
+    pub fn issuer(&self) -> VerifyingKey {
+        self.issuer.verifying_key()
+    }
+
+    /// Issues `capability` to `audience`, like
+    /// [`RcanBuilder::sign`](crate::RcanBuilder::sign) on an issuing
+    /// builder.
+    pub fn issue<C: Serialize>(
+        &self,
+        audience: VerifyingKey,
+        capability: C,
+        valid_until: Expires,
+    ) -> Rcan<C> {
+        let (issuer, payload) =
+            Rcan::issuing_builder(self.issuer, audience, capability).into_payload(valid_until);
+        let to_sign = signing_preimage(&payload, None);
+        let signature = raw_sign::<Sha512>(&self.expanded, &to_sign, &issuer.verifying_key());
+
+        Rcan { signature, payload }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::test::Rpc;
+
+    #[test]
+    fn test_batch_issuer() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let batch = BatchIssuer::new(&issuer);
+        let tokens: Vec<_> = (0..100u8)
+            .map(|i| {
+                let audience = SigningKey::from_bytes(&[i; 32]).verifying_key();
+                batch.issue(audience, Rpc::Read, Expires::Never)
+            })
+            .collect();
+
+        for token in tokens.iter().step_by(10) {
+            token.verify_self()?;
+            assert_eq!(Rcan::decode(&token.encode())?, *token);
+            // The same signature as signing with the key directly.
+            assert_eq!(Rcan::sign_payload(&issuer, token.payload.clone()), *token);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1779 @@
+//! Ready-made [`Capability`] implementations for common authorization shapes.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec::Vec,
+};
+
+#[cfg(feature = "std")]
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use crate::{Authorizer, InvocationProof, Position, Rcan, RcanId};
+use crate::{BoxError, Capability, RcanError, Result, Witness};
+
+/// A capability fenced by a monotonic configuration epoch.
+///
+/// A holder of epoch `N` may act for any epoch `<= N`. Authorizers pin the
+/// epoch they are currently at with
+/// [`Authorizer::check_epoch_invocation_from`], so tokens minted for an
+/// older epoch stop verifying as soon as the epoch advances.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EpochCapability(pub u64);
+
+impl Capability for EpochCapability {
+    fn permits(&self, other: &Self) -> bool {
+        other.0 <= self.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl Authorizer {
+    /// Verifies an invocation against the authorizer's `current_epoch`.
+    ///
+    /// Every proof in the chain must have been granted for `current_epoch`
+    /// or later, so delegations from a stale epoch are rejected.
+    pub fn check_epoch_invocation_from(
+        &self,
+        invoker: VerifyingKey,
+        current_epoch: u64,
+        proof_chain: &[&Rcan<EpochCapability>],
+    ) -> Result<InvocationProof> {
+        self.check_invocation_from(invoker, EpochCapability(current_epoch), proof_chain)
+    }
+}
+
+/// Access to a resource, where `Own > Write > Read` and each level permits
+/// the ones below it.
+///
+/// Only owners may pass access on: a token granting `Read` or `Write` can't
+/// be re-delegated, see [`Capability::may_delegate`]. An owner may
+/// delegate any level, including `Own`, to transfer ownership.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccessCapability {
+    /// Reading the resource.
+    Read,
+    /// Reading and modifying the resource.
+    Write,
+    /// Full control, including passing access on.
+    Own,
+}
+
+impl Capability for AccessCapability {
+    fn permits(&self, other: &Self) -> bool {
+        self >= other
+    }
+
+    fn may_delegate(&self) -> bool {
+        *self == AccessCapability::Own
+    }
+}
+
+/// A numeric privilege level, where a higher level permits every lower
+/// one.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LevelCapability(pub u32);
+
+impl Capability for LevelCapability {
+    fn permits(&self, other: &Self) -> bool {
+        self.0 >= other.0
+    }
+}
+
+/// Access to a range of bytes within an object.
+///
+/// The range is half-open: `start` is inclusive and `end` exclusive, so
+/// `{ start: 0, end: Some(10) }` covers bytes `0..10`. An `end` of `None`
+/// extends the range to the end of the object.
+///
+/// A range permits another if it fully contains it. Ranges with
+/// `end < start` are malformed, permit nothing and are permitted by nothing.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ByteRangeCapability {
+    /// First byte of the range, inclusive.
+    pub start: u64,
+    /// End of the range, exclusive, or `None` for "to EOF".
+    pub end: Option<u64>,
+}
+
+impl ByteRangeCapability {
+    fn is_well_formed(&self) -> bool {
+        self.end.is_none_or(|end| self.start <= end)
+    }
+}
+
+impl Capability for ByteRangeCapability {
+    fn permits(&self, other: &Self) -> bool {
+        if !self.is_well_formed() || !other.is_well_formed() || other.start < self.start {
+            return false;
+        }
+        match (self.end, other.end) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(end), Some(other_end)) => other_end <= end,
+        }
+    }
+}
+
+/// Access to a dotted configuration namespace, like `service.db.host`.
+///
+/// A trailing `*` segment is a wildcard matching one or more further
+/// segments, so `service.db.*` permits `service.db.host` and
+/// `service.db.replica.*`, but neither `service.db` itself nor
+/// `service.cache.host`. Patterns without a wildcard only permit
+/// themselves.
+///
+/// Patterns must consist of non-empty segments, and `*` may only appear
+/// as the whole last segment. Malformed patterns are rejected by
+/// [`NamespaceCapability::new`] and on deserialization.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct NamespaceCapability(String);
+
+impl NamespaceCapability {
+    /// Parses a namespace pattern.
+    pub fn new(pattern: impl Into<String>) -> Result<Self> {
+        let pattern = pattern.into();
+        let segments: Vec<&str> = pattern.split('.').collect();
+        for (i, segment) in segments.iter().enumerate() {
+            if segment.is_empty() {
+                return Err(RcanError::InvalidCapability(format!(
+                    "invalid namespace {pattern:?}: empty segment"
+                )));
+            }
+            if segment.contains('*') && (*segment != "*" || i != segments.len() - 1) {
+                return Err(RcanError::InvalidCapability(format!(
+                    "invalid namespace {pattern:?}: `*` is only allowed as the last segment"
+                )));
+            }
+        }
+        Ok(Self(pattern))
+    }
+
+    /// The pattern as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for NamespaceCapability {
+    type Error = RcanError;
+
+    fn try_from(pattern: String) -> Result<Self> {
+        Self::new(pattern)
+    }
+}
+
+impl From<NamespaceCapability> for String {
+    fn from(namespace: NamespaceCapability) -> Self {
+        namespace.0
+    }
+}
+
+impl Capability for NamespaceCapability {
+    fn permits(&self, other: &Self) -> bool {
+        match self.0.strip_suffix('*') {
+            // `prefix` keeps its trailing dot, so this matches whole segments.
+            Some(prefix) => other.0.len() > prefix.len() && other.0.starts_with(prefix),
+            None => self.0 == other.0,
+        }
+    }
+}
+
+/// A DNS domain and all of its subdomains.
+///
+/// `example.com` permits `example.com` and `api.example.com`, but not
+/// `notexample.com`. Names are compared case-insensitively, and a single
+/// trailing dot marking a fully qualified name is ignored, so
+/// `Example.COM.` permits `api.example.com`. An empty name is malformed and
+/// permits nothing.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct DomainCapability(pub String);
+
+impl DomainCapability {
+    /// The name in lowercase, without a trailing dot.
+    fn normalized(&self) -> String {
+        let name = self.0.strip_suffix('.').unwrap_or(&self.0);
+        name.to_ascii_lowercase()
+    }
+}
+
+impl Capability for DomainCapability {
+    fn permits(&self, other: &Self) -> bool {
+        let (domain, other) = (self.normalized(), other.normalized());
+        if domain.is_empty() || other.is_empty() {
+            return false;
+        }
+        match other.strip_suffix(&domain) {
+            Some("") => true,
+            // `sub` must end at a label boundary.
+            Some(sub) => sub.len() > 1 && sub.ends_with('.'),
+            None => false,
+        }
+    }
+}
+
+/// A grant with explicit exceptions that override it.
+///
+/// Models "allow `grant`, but never anything in `deny`, even if `grant`
+/// would cover it". A request is a `GuardedCapability` as well, usually
+/// built with [`GuardedCapability::new`] and no exceptions of its own, and
+/// is permitted if `grant` permits its grant and no `deny` entry overlaps
+/// it. An entry overlaps if it permits the requested grant, or if the
+/// requested grant permits it, so asking for something *broader* than a
+/// denied capability is denied too.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct GuardedCapability<C> {
+    /// What is granted.
+    pub grant: C,
+    /// Exceptions that are never granted.
+    pub deny: Vec<C>,
+}
+
+impl<C> GuardedCapability<C> {
+    /// A grant without any exceptions.
+    pub fn new(grant: C) -> Self {
+        Self {
+            grant,
+            deny: Vec::new(),
+        }
+    }
+
+    /// Adds an exception to the grant.
+    pub fn deny(mut self, capability: C) -> Self {
+        self.deny.push(capability);
+        self
+    }
+}
+
+impl<C: Capability> Capability for GuardedCapability<C> {
+    fn permits(&self, other: &Self) -> bool {
+        self.grant.permits(&other.grant)
+            && !self
+                .deny
+                .iter()
+                .any(|denied| denied.permits(&other.grant) || other.grant.permits(denied))
+    }
+
+    fn may_delegate(&self) -> bool {
+        self.grant.may_delegate()
+    }
+}
+
+/// Access to a selection of fields, GraphQL style.
+///
+/// The selection is a tree of field names, written like
+/// `{user{name,email},posts}` with [`core::str::FromStr`]. A selection
+/// permits another if the other is a sub-tree of it: every field it selects
+/// is selected here, recursively. A field selected without sub-fields is
+/// a leaf, so `{user}` doesn't permit `{user{name}}`.
+///
+/// Fields are kept sorted, so the serialized form is deterministic.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct FieldSelectionCapability(BTreeMap<String, FieldSelectionCapability>);
+
+impl FieldSelectionCapability {
+    /// Adds a field with its sub-selection.
+    pub fn field(mut self, name: impl Into<String>, selection: FieldSelectionCapability) -> Self {
+        self.0.insert(name.into(), selection);
+        self
+    }
+
+    /// Parses the fields of a selection up to and including its closing `}`.
+    fn parse_fields(input: &mut core::iter::Peekable<core::str::Chars<'_>>) -> Result<Self> {
+        let mut selection = Self::default();
+        loop {
+            let mut name = String::new();
+            while let Some(c) = input.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                name.push(c);
+            }
+            if name.is_empty() {
+                // Only `{}` may have no fields.
+                if !selection.0.is_empty() || input.next_if_eq(&'}').is_none() {
+                    return Err(invalid_selection("expected a field name"));
+                }
+                return Ok(selection);
+            }
+            let sub_selection = match input.next_if_eq(&'{') {
+                Some(_) => Self::parse_fields(input)?,
+                None => Self::default(),
+            };
+            if selection.0.insert(name.clone(), sub_selection).is_some() {
+                return Err(invalid_selection(format!("duplicate field {name:?}")));
+            }
+            match input.next() {
+                Some('}') => return Ok(selection),
+                Some(',') => {}
+                Some(c) => return Err(invalid_selection(format!("unexpected {c:?}"))),
+                None => return Err(invalid_selection("unterminated")),
+            }
+        }
+    }
+}
+
+/// A parse error of a field selection.
+fn invalid_selection(reason: impl core::fmt::Display) -> RcanError {
+    RcanError::InvalidCapability(format!("invalid field selection: {reason}"))
+}
+
+impl core::str::FromStr for FieldSelectionCapability {
+    type Err = RcanError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let stripped: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut input = stripped.chars().peekable();
+        if input.next() != Some('{') {
+            return Err(invalid_selection("must start with `{`"));
+        }
+        let selection = Self::parse_fields(&mut input)?;
+        if input.next().is_some() {
+            return Err(invalid_selection("trailing input"));
+        }
+        Ok(selection)
+    }
+}
+
+impl core::fmt::Display for FieldSelectionCapability {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("{")?;
+        for (i, (name, selection)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(name)?;
+            if !selection.0.is_empty() {
+                write!(f, "{selection}")?;
+            }
+        }
+        f.write_str("}")
+    }
+}
+
+impl Capability for FieldSelectionCapability {
+    fn permits(&self, other: &Self) -> bool {
+        other.0.iter().all(|(name, other_selection)| {
+            self.0
+                .get(name)
+                .is_some_and(|selection| selection.permits(other_selection))
+        })
+    }
+}
+
+/// Feature toggles, by name.
+///
+/// Toggles are denied by default: a grant permits a request if every
+/// toggle the request enables is enabled in the grant. Toggles that are
+/// absent or explicitly `false` in the grant are denied, and a request
+/// may always ask for a toggle to be off.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct TogglesCapability(pub BTreeMap<String, bool>);
+
+impl Capability for TogglesCapability {
+    fn permits(&self, other: &Self) -> bool {
+        other.enabled().all(|name| self.0.get(name) == Some(&true))
+    }
+
+    /// The toggles enabled in both.
+    fn meet(&self, other: &Self) -> Option<Self> {
+        Some(Self(
+            self.enabled()
+                .filter(|name| other.0.get(*name) == Some(&true))
+                .map(|name| (name.clone(), true))
+                .collect(),
+        ))
+    }
+
+    /// The toggles enabled in either.
+    fn join(&self, other: &Self) -> Option<Self> {
+        Some(Self(
+            self.enabled()
+                .chain(other.enabled())
+                .map(|name| (name.clone(), true))
+                .collect(),
+        ))
+    }
+}
+
+impl TogglesCapability {
+    /// The names of the enabled toggles.
+    fn enabled(&self) -> impl Iterator<Item = &String> {
+        self.0
+            .iter()
+            .filter(|(_, enabled)| **enabled)
+            .map(|(name, _)| name)
+    }
+}
+
+/// Calls of RPC methods by name, for services with a dynamic set of
+/// methods. The string analog of a fixed enum of methods.
+///
+/// A set of methods permits its subsets, [`MethodNameCapability::All`]
+/// permits every method, including ones registered later.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum MethodNameCapability {
+    /// Only these methods.
+    Methods(BTreeSet<String>),
+    /// Every method.
+    All,
+}
+
+impl Capability for MethodNameCapability {
+    fn permits(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::All, _) => true,
+            (Self::Methods(_), Self::All) => false,
+            (Self::Methods(granted), Self::Methods(requested)) => requested.is_subset(granted),
+        }
+    }
+
+    /// The methods in both.
+    fn meet(&self, other: &Self) -> Option<Self> {
+        Some(match (self, other) {
+            (Self::All, other) | (other, Self::All) => other.clone(),
+            (Self::Methods(a), Self::Methods(b)) => {
+                Self::Methods(a.intersection(b).cloned().collect())
+            }
+        })
+    }
+
+    /// The methods in either.
+    fn join(&self, other: &Self) -> Option<Self> {
+        Some(match (self, other) {
+            (Self::All, _) | (_, Self::All) => Self::All,
+            (Self::Methods(a), Self::Methods(b)) => Self::Methods(a.union(b).cloned().collect()),
+        })
+    }
+}
+
+/// A set of capabilities, granting everything any of its elements grants.
+///
+/// A set permits another if each of the other's elements is permitted by
+/// some element of this set. [`Capability::permits_with_witness`] names the
+/// granting element for each requested one.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct SetCapability<C>(pub Vec<C>);
+
+impl<C: Capability> SetCapability<C> {
+    fn granting(&self, requested: &C) -> Option<&C> {
+        self.0.iter().find(|granted| granted.permits(requested))
+    }
+}
+
+impl<C: Capability + core::fmt::Debug> Capability for SetCapability<C> {
+    fn permits(&self, other: &Self) -> bool {
+        other
+            .0
+            .iter()
+            .all(|requested| self.granting(requested).is_some())
+    }
+
+    fn permits_with_witness(&self, other: &Self) -> Option<Witness> {
+        let explanations = other
+            .0
+            .iter()
+            .map(|requested| {
+                let granted = self.granting(requested)?;
+                Some(format!("{granted:?} permits {requested:?}"))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Witness::new(explanations.join(", ")))
+    }
+}
+
+/// A map of capabilities, attenuated by dropping keys or narrowing values.
+///
+/// A map permits another if every key of the other is also in this map,
+/// with a value that permits the other's value. Nest `MapCapability`s as
+/// values for structured, object-like capabilities.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct MapCapability<K: Ord, V>(pub BTreeMap<K, V>);
+
+impl<K: Ord + Serialize, V: Capability> Capability for MapCapability<K, V> {
+    fn permits(&self, other: &Self) -> bool {
+        other.0.iter().all(|(key, other_value)| {
+            self.0
+                .get(key)
+                .is_some_and(|value| value.permits(other_value))
+        })
+    }
+}
+
+/// A method call with constraints on its arguments, like `transfer` only
+/// to allow-listed recipients.
+///
+/// Permits another call only if `method` permits the other's method *and*
+/// `args` permits its arguments.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ConstrainedMethodCapability<M, A> {
+    /// Which methods may be called.
+    pub method: M,
+    /// Which arguments they may be called with.
+    pub args: A,
+}
+
+impl<M: Capability, A: Capability> Capability for ConstrainedMethodCapability<M, A> {
+    fn permits(&self, other: &Self) -> bool {
+        self.method.permits(&other.method) && self.args.permits(&other.args)
+    }
+}
+
+/// The id of a tenant in a multi-tenant service, see [`TenantScoped`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, derive_more::Display)]
+pub struct TenantId(pub String);
+
+/// A capability confined to one tenant.
+///
+/// Permits another only within the same `tenant`, and only if `inner`
+/// permits the other's inner capability. Invocations across tenants always
+/// fail, however broad the inner capability is.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct TenantScoped<C> {
+    /// The tenant the capability is confined to.
+    pub tenant: TenantId,
+    /// The capability within the tenant.
+    pub inner: C,
+}
+
+impl<C: Capability> Capability for TenantScoped<C> {
+    fn permits(&self, other: &Self) -> bool {
+        self.tenant == other.tenant && self.inner.permits(&other.inner)
+    }
+
+    fn may_delegate(&self) -> bool {
+        self.inner.may_delegate()
+    }
+}
+
+/// A rate limit of at most `rate` requests per `window_secs` seconds.
+///
+/// A limit permits another if the other can never exceed it. Both are
+/// normalized to `self`'s window: the other window must not be larger, and
+/// `other.rate` times the number of other windows needed to cover `self`'s
+/// window (rounded up) must not exceed `self.rate`. So `100` per `60s`
+/// permits `10` per `10s`, but neither `17` per `10s` nor `1000` per
+/// `600s`, which would allow a 1000 request burst. Windows are assumed to
+/// be aligned. A zero window is malformed and permits nothing.
+///
+/// This only bounds what may be delegated. Enforcing the limit requires the
+/// service to count requests per token.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct RateCapability {
+    /// Maximum number of requests per window.
+    pub rate: u32,
+    /// Length of the window in seconds.
+    pub window_secs: u32,
+}
+
+impl Capability for RateCapability {
+    fn permits(&self, other: &Self) -> bool {
+        if self.window_secs == 0 || other.window_secs == 0 || other.window_secs > self.window_secs {
+            return false;
+        }
+        let windows = u64::from(self.window_secs.div_ceil(other.window_secs));
+        u64::from(other.rate) * windows <= u64::from(self.rate)
+    }
+}
+
+/// A rate limit with bursts, enforced at invocation like a leaky bucket.
+///
+/// The bucket of a token holds up to `burst` invocations and starts out
+/// full. Each invocation takes one, and `refill_per_sec` are added back
+/// every second. Enforced with a [`BucketStore`] by
+/// [`Authorizer::check_bucket_invocation_from`], for the bucket of every
+/// proof in the chain, so delegations share their parent's bucket.
+///
+/// A bucket permits any bucket at most as large and refilling at most as
+/// fast.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct LeakyBucketCapability {
+    /// Maximum number of invocations in a burst.
+    pub burst: u32,
+    /// Invocations added back to the bucket per second.
+    pub refill_per_sec: u32,
+}
+
+impl Capability for LeakyBucketCapability {
+    fn permits(&self, other: &Self) -> bool {
+        other.burst <= self.burst && other.refill_per_sec <= self.refill_per_sec
+    }
+}
+
+/// The buckets of [`LeakyBucketCapability`] tokens, by token id.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct BucketStore {
+    buckets: BTreeMap<RcanId, Bucket>,
+}
+
+/// The state of one bucket, as of its last invocation.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    level: u32,
+    /// In unix seconds.
+    updated_at: u64,
+}
+
+#[cfg(feature = "std")]
+impl BucketStore {
+    /// Creates a store where every bucket is full.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bucket of `id` for `capability` at `now`, refilled since its
+    /// last invocation.
+    fn refilled(&self, id: &RcanId, capability: &LeakyBucketCapability, now: u64) -> Bucket {
+        let level = match self.buckets.get(id) {
+            None => capability.burst,
+            Some(bucket) => {
+                let elapsed = now.saturating_sub(bucket.updated_at);
+                let refill = elapsed.saturating_mul(u64::from(capability.refill_per_sec));
+                let level = u64::from(bucket.level).saturating_add(refill);
+                level.min(u64::from(capability.burst)) as u32
+            }
+        };
+        Bucket {
+            level,
+            updated_at: now,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Authorizer {
+    /// Verifies an invocation of a [`LeakyBucketCapability`] now, see
+    /// [`Authorizer::check_bucket_invocation_from_at`].
+    pub fn check_bucket_invocation_from(
+        &self,
+        buckets: &mut BucketStore,
+        invoker: VerifyingKey,
+        proof_chain: &[&Rcan<LeakyBucketCapability>],
+    ) -> Result<InvocationProof> {
+        self.check_bucket_invocation_from_at(
+            crate::SystemTime::now(),
+            buckets,
+            invoker,
+            proof_chain,
+        )
+    }
+
+    /// Verifies an invocation of a [`LeakyBucketCapability`] at `at`, and
+    /// takes one invocation from the bucket of every proof in the chain.
+    ///
+    /// On top of the checks of [`Authorizer::check_invocation_from_at`],
+    /// the bucket of every proof must not be empty. Nothing is taken from
+    /// any bucket if one of them is. Each proof holds exactly one bucket,
+    /// proofs with several fail with [`RcanError::AmbiguousBucket`].
+    ///
+    /// Buckets refill by the second, so `at` must be a
+    /// [`Position::Time`], checking at a [`Position::Block`] fails with
+    /// [`RcanError::BucketAtBlock`].
+    pub fn check_bucket_invocation_from_at(
+        &self,
+        at: impl Into<Position>,
+        buckets: &mut BucketStore,
+        invoker: VerifyingKey,
+        proof_chain: &[&Rcan<LeakyBucketCapability>],
+    ) -> Result<InvocationProof> {
+        // A single invocation, the buckets enforce the rate.
+        let invocation = LeakyBucketCapability {
+            burst: 1,
+            refill_per_sec: 0,
+        };
+        let at = at.into();
+        let Position::Time(now) = at else {
+            return Err(RcanError::BucketAtBlock);
+        };
+        if let Some(index) = proof_chain
+            .iter()
+            .position(|proof| proof.capabilities().len() > 1)
+        {
+            return Err(RcanError::AmbiguousBucket { index });
+        }
+        let proof = self.check_invocation_from_at(at, invoker, invocation, proof_chain)?;
+
+        let refilled: Vec<_> = proof_chain
+            .iter()
+            .map(|proof| {
+                let id = proof.id();
+                let bucket = buckets.refilled(&id, proof.capability(), now);
+                (id, bucket)
+            })
+            .collect();
+        if let Some(index) = refilled.iter().position(|(_, bucket)| bucket.level == 0) {
+            return Err(RcanError::BucketEmpty { index });
+        }
+        for (id, mut bucket) in refilled {
+            bucket.level -= 1;
+            buckets.buckets.insert(id, bucket);
+        }
+        Ok(proof)
+    }
+}
+
+/// A fractional share of a resource, like "up to 30% of bandwidth", in
+/// thousandths.
+///
+/// A share permits any share at most as large, so sub-delegations can only
+/// shrink it. Shares above `1000` are not rejected, but grant no more than
+/// the whole resource in practice.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShareCapability {
+    /// The share in thousandths, so `300` is 30%.
+    pub fraction_millis: u32,
+}
+
+impl Capability for ShareCapability {
+    fn permits(&self, other: &Self) -> bool {
+        other.fraction_millis <= self.fraction_millis
+    }
+}
+
+/// A share of a budget, like credits or bytes, that depletes as it is
+/// delegated down a chain.
+///
+/// The root proof of a chain grants the total budget. Every delegation
+/// after it reserves an amount out of that same total for its audience,
+/// which the intermediaries don't pass on. The reservations in a chain must
+/// add up to at most the root budget, so the leaf can spend at most what is
+/// left after everyone before it took their share. This is a property of
+/// the whole chain, checked by
+/// [`Authorizer::check_budget_invocation_from`]; `permits` alone only
+/// compares amounts pairwise.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DepletingBudgetCapability(pub u64);
+
+impl Capability for DepletingBudgetCapability {
+    fn permits(&self, other: &Self) -> bool {
+        other.0 <= self.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl Authorizer {
+    /// Verifies spending `amount` of a [`DepletingBudgetCapability`].
+    ///
+    /// On top of the checks of [`Authorizer::check_invocation_from`], the
+    /// reservations of all delegations after the root must add up to at
+    /// most the root budget, and `amount` must fit in the invoker's own
+    /// reservation. Each proof holds exactly one budget, proofs with
+    /// several fail with [`RcanError::AmbiguousBudget`].
+    pub fn check_budget_invocation_from(
+        &self,
+        invoker: VerifyingKey,
+        amount: u64,
+        proof_chain: &[&Rcan<DepletingBudgetCapability>],
+    ) -> Result<InvocationProof> {
+        if let Some(index) = proof_chain
+            .iter()
+            .position(|proof| proof.capabilities().len() != 1)
+        {
+            return Err(RcanError::AmbiguousBudget { index });
+        }
+        let proof =
+            self.check_invocation_from(invoker, DepletingBudgetCapability(amount), proof_chain)?;
+
+        let Some((root, delegations)) = proof_chain.split_first() else {
+            return Ok(proof);
+        };
+        let reserved = delegations
+            .iter()
+            .try_fold(0u64, |sum, proof| sum.checked_add(proof.capability().0));
+        if reserved.is_none_or(|reserved| reserved > root.capability().0) {
+            return Err(RcanError::BudgetExceeded {
+                budget: root.capability().0,
+            });
+        }
+        Ok(proof)
+    }
+}
+
+/// A reference to a policy document kept outside the token, by the
+/// BLAKE3 hash of the document.
+///
+/// For policies too complex to encode as a capability. The policy is
+/// loaded and evaluated by a [`PolicyResolver`], in
+/// [`Authorizer::check_policy_invocation_from`]. Without a resolver,
+/// `permits` only holds between references to the same policy.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PolicyRefCapability {
+    pub policy_hash: [u8; 32],
+}
+
+impl Capability for PolicyRefCapability {
+    fn permits(&self, other: &Self) -> bool {
+        self.policy_hash == other.policy_hash
+    }
+}
+
+/// Loads and evaluates the policies referenced by
+/// [`PolicyRefCapability`]s.
+pub trait PolicyResolver {
+    /// What the policies are evaluated against.
+    type Request: ?Sized;
+
+    /// Whether the policy with `policy_hash` permits `request`.
+    ///
+    /// Errors, e.g. for an unknown policy, fail the invocation.
+    fn evaluate(&self, policy_hash: &[u8; 32], request: &Self::Request) -> Result<bool, BoxError>;
+
+    /// Whether the policy `outer` permits everything `inner` permits, so a
+    /// token with `outer` may be delegated further with `inner`.
+    ///
+    /// Defaults to only a policy subsuming itself.
+    fn subsumes(&self, outer: &[u8; 32], inner: &[u8; 32]) -> Result<bool, BoxError> {
+        Ok(outer == inner)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Authorizer {
+    /// Verifies an invocation of `request` under external policies.
+    ///
+    /// On top of the chain checks of [`Authorizer::check_invocation_from`],
+    /// every delegation's policies must be subsumed by one of its parent's,
+    /// and every proof must have a policy permitting `request`, as decided
+    /// by `resolver`.
+    pub fn check_policy_invocation_from<P: PolicyResolver>(
+        &self,
+        invoker: VerifyingKey,
+        request: &P::Request,
+        proof_chain: &[&Rcan<PolicyRefCapability>],
+        resolver: &P,
+    ) -> Result<InvocationProof> {
+        self.check_chain_integrity(crate::SystemTime::now().into(), invoker, proof_chain)?;
+
+        for (i, pair) in proof_chain.windows(2).enumerate() {
+            for inner in pair[1].capabilities() {
+                let mut subsumed = false;
+                for outer in pair[0].capabilities() {
+                    subsumed = resolver
+                        .subsumes(&outer.policy_hash, &inner.policy_hash)
+                        .map_err(|source| RcanError::PolicyEvaluation {
+                            index: i + 1,
+                            source,
+                        })?;
+                    if subsumed {
+                        break;
+                    }
+                }
+                if !subsumed {
+                    return Err(RcanError::BrokenLink { index: i });
+                }
+            }
+        }
+
+        for (i, proof) in proof_chain.iter().enumerate() {
+            let mut permitted = false;
+            for policy in proof.capabilities() {
+                permitted = resolver
+                    .evaluate(&policy.policy_hash, request)
+                    .map_err(|source| RcanError::PolicyEvaluation { index: i, source })?;
+                if permitted {
+                    break;
+                }
+            }
+            if !permitted {
+                return Err(RcanError::CapabilityNotPermitted { proof: i });
+            }
+        }
+
+        Ok(InvocationProof::for_chain(proof_chain))
+    }
+}
+
+/// A version tag for one wire schema of a capability type, used with
+/// [`Versioned`].
+pub trait SchemaVersion {
+    /// The tag written in front of capabilities in this schema.
+    const VERSION: u8;
+}
+
+/// A capability whose wire schema is being migrated from `Old` to `New`.
+///
+/// Serializes as a `(version, capability)` tuple, where `version` is the
+/// [`SchemaVersion::VERSION`] of the schema the capability is in. Decoding
+/// reads the tag first and picks the matching deserializer, so chains can
+/// mix links minted before and after a rollout. Each link re-serializes in
+/// its own schema, keeping its signature valid.
+///
+/// Capabilities are compared in the `New` schema, converting `Old` ones
+/// with [`Into`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Versioned<Old, New> {
+    /// A capability in the old schema.
+    Old(Old),
+    /// A capability in the new schema.
+    New(New),
+}
+
+impl<Old: Clone + Into<New>, New: Clone> Versioned<Old, New> {
+    /// The capability in the new schema.
+    pub fn to_current(&self) -> New {
+        match self {
+            Versioned::Old(old) => old.clone().into(),
+            Versioned::New(new) => new.clone(),
+        }
+    }
+}
+
+impl<Old, New> Serialize for Versioned<Old, New>
+where
+    Old: Serialize + SchemaVersion,
+    New: Serialize + SchemaVersion,
+{
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+        let mut tup = serializer.serialize_tuple(2)?;
+        match self {
+            Versioned::Old(old) => {
+                tup.serialize_element(&Old::VERSION)?;
+                tup.serialize_element(old)?;
+            }
+            Versioned::New(new) => {
+                tup.serialize_element(&New::VERSION)?;
+                tup.serialize_element(new)?;
+            }
+        }
+        tup.end()
+    }
+}
+
+impl<'de, Old, New> Deserialize<'de> for Versioned<Old, New>
+where
+    Old: Deserialize<'de> + SchemaVersion,
+    New: Deserialize<'de> + SchemaVersion,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        struct V<Old, New>(core::marker::PhantomData<(Old, New)>);
+
+        impl<'de, Old, New> serde::de::Visitor<'de> for V<Old, New>
+        where
+            Old: Deserialize<'de> + SchemaVersion,
+            New: Deserialize<'de> + SchemaVersion,
+        {
+            type Value = Versioned<Old, New>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(
+                    f,
+                    "a capability tagged with schema version {} or {}",
+                    Old::VERSION,
+                    New::VERSION
+                )
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> core::result::Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                use serde::de::Error;
+                let version: u8 = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+                let capability = if version == New::VERSION {
+                    seq.next_element()?.map(Versioned::New)
+                } else if version == Old::VERSION {
+                    seq.next_element()?.map(Versioned::Old)
+                } else {
+                    return Err(A::Error::custom(format_args!(
+                        "unknown capability schema version {version}"
+                    )));
+                };
+                capability.ok_or_else(|| A::Error::invalid_length(1, &self))
+            }
+        }
+
+        deserializer.deserialize_tuple(2, V(core::marker::PhantomData))
+    }
+}
+
+impl<Old, New> Capability for Versioned<Old, New>
+where
+    Old: Serialize + SchemaVersion + Clone + Into<New>,
+    New: Capability + SchemaVersion + Clone,
+{
+    fn permits(&self, other: &Self) -> bool {
+        self.to_current().permits(&other.to_current())
+    }
+
+    fn may_delegate(&self) -> bool {
+        self.to_current().may_delegate()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use ed25519_dalek::SigningKey;
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::{test::Rpc, Expires};
+
+    #[test]
+    fn test_epoch_capability() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let auth = Authorizer::new(service.verifying_key());
+        let rcan = Rcan::issuing_builder(&service, alice.verifying_key(), EpochCapability(5))
+            .sign(Expires::Never);
+
+        // equal epoch
+        assert!(auth
+            .check_epoch_invocation_from(alice.verifying_key(), 5, &[&rcan])
+            .is_ok());
+        // the authorizer is at an older epoch than the grant
+        assert!(auth
+            .check_epoch_invocation_from(alice.verifying_key(), 4, &[&rcan])
+            .is_ok());
+        // the epoch moved on, the grant is stale
+        assert!(auth
+            .check_epoch_invocation_from(alice.verifying_key(), 6, &[&rcan])
+            .is_err());
+    }
+
+    #[test]
+    fn test_level_capability() {
+        assert!(LevelCapability(5).permits(&LevelCapability(3)));
+        assert!(LevelCapability(5).permits(&LevelCapability(5)));
+        assert!(!LevelCapability(5).permits(&LevelCapability(7)));
+    }
+
+    #[test]
+    fn test_byte_range_capability() {
+        let range = |start, end| ByteRangeCapability { start, end };
+
+        // contained
+        assert!(range(0, Some(100)).permits(&range(10, Some(20))));
+        assert!(range(0, Some(100)).permits(&range(0, Some(100))));
+        // overlapping, but not contained
+        assert!(!range(0, Some(100)).permits(&range(50, Some(150))));
+        assert!(!range(10, Some(100)).permits(&range(0, Some(20))));
+        // open-ended
+        assert!(range(10, None).permits(&range(20, Some(1 << 40))));
+        assert!(range(10, None).permits(&range(20, None)));
+        assert!(!range(0, Some(100)).permits(&range(50, None)));
+        // malformed
+        assert!(!range(0, None).permits(&range(20, Some(10))));
+    }
+
+    #[test]
+    fn test_namespace_capability() -> TestResult {
+        let ns = |pattern: &str| NamespaceCapability::new(pattern).unwrap();
+
+        // wildcard
+        assert!(ns("service.db.*").permits(&ns("service.db.host")));
+        assert!(ns("service.db.*").permits(&ns("service.db.replica.host")));
+        assert!(ns("service.db.*").permits(&ns("service.db.replica.*")));
+        assert!(ns("*").permits(&ns("service.db.host")));
+        assert!(!ns("service.db.*").permits(&ns("service.cache.host")));
+        assert!(!ns("service.db.host").permits(&ns("service.db.*")));
+        // exact match
+        assert!(ns("service.db.host").permits(&ns("service.db.host")));
+        assert!(!ns("service.db.host").permits(&ns("service.db.port")));
+        // prefix boundaries
+        assert!(!ns("service.db.*").permits(&ns("service.db")));
+        assert!(!ns("service.db.*").permits(&ns("service.dbx.host")));
+        assert!(!ns("service.db").permits(&ns("service.db.host")));
+
+        // malformed
+        for pattern in [
+            "",
+            "service..db",
+            "service.db.",
+            ".service",
+            "service.d*",
+            "*.db",
+        ] {
+            assert!(NamespaceCapability::new(pattern).is_err(), "{pattern:?}");
+        }
+        assert!(
+            postcard::from_bytes::<NamespaceCapability>(&postcard::to_allocvec("a..b")?).is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_domain_capability() {
+        let domain = |name: &str| DomainCapability(name.to_string());
+        let granted = domain("example.com");
+
+        // exact
+        assert!(granted.permits(&domain("example.com")));
+        assert!(granted.permits(&domain("EXAMPLE.com.")));
+        assert!(domain("Example.COM.").permits(&domain("example.com")));
+        // subdomains
+        assert!(granted.permits(&domain("api.example.com")));
+        assert!(granted.permits(&domain("a.b.example.com.")));
+        // non-matching suffixes and parents
+        assert!(!granted.permits(&domain("notexample.com")));
+        assert!(!granted.permits(&domain(".example.com")));
+        assert!(!granted.permits(&domain("com")));
+        assert!(!domain("api.example.com").permits(&granted));
+        // malformed
+        assert!(!domain("").permits(&granted));
+        assert!(!domain(".").permits(&granted));
+        assert!(!granted.permits(&domain("")));
+    }
+
+    #[test]
+    fn test_guarded_capability() {
+        #[derive(Serialize)]
+        enum Op {
+            Read,
+            Admin,
+            All,
+        }
+
+        impl Capability for Op {
+            fn permits(&self, other: &Self) -> bool {
+                matches!(
+                    (self, other),
+                    (Op::All, _) | (Op::Read, Op::Read) | (Op::Admin, Op::Admin)
+                )
+            }
+        }
+
+        let guarded = GuardedCapability::new(Op::All).deny(Op::Admin);
+        assert!(guarded.permits(&GuardedCapability::new(Op::Read)));
+        assert!(!guarded.permits(&GuardedCapability::new(Op::Admin)));
+        // `All` would include `Admin`
+        assert!(!guarded.permits(&GuardedCapability::new(Op::All)));
+    }
+
+    #[test]
+    fn test_field_selection_capability() -> TestResult {
+        let granted: FieldSelectionCapability = "{user{name,email}}".parse()?;
+
+        assert!(granted.permits(&"{user{name}}".parse()?));
+        assert!(granted.permits(&"{ user { email, name } }".parse()?));
+        assert!(granted.permits(&"{}".parse()?));
+        assert!(!granted.permits(&"{user{address}}".parse()?));
+        assert!(!granted.permits(&"{user{name{first}}}".parse()?));
+        assert!(!granted.permits(&"{posts}".parse()?));
+
+        // deterministic
+        assert_eq!(granted.to_string(), "{user{email,name}}");
+        let built = FieldSelectionCapability::default().field(
+            "user",
+            FieldSelectionCapability::default()
+                .field("name", Default::default())
+                .field("email", Default::default()),
+        );
+        assert_eq!(built, granted);
+        assert_eq!(
+            postcard::to_allocvec(&built)?,
+            postcard::to_allocvec(&granted)?
+        );
+
+        for malformed in [
+            "", "user", "{user", "{user,}", "{,}", "{user}}", "{a,a}", "{a-b}",
+        ] {
+            assert!(
+                malformed.parse::<FieldSelectionCapability>().is_err(),
+                "{malformed:?}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_capability_witness() -> TestResult {
+        #[derive(Debug, Serialize)]
+        enum Op {
+            Read,
+            Write,
+            Admin,
+        }
+
+        impl Capability for Op {
+            fn permits(&self, other: &Self) -> bool {
+                matches!(
+                    (self, other),
+                    (Op::Admin, _) | (Op::Read, Op::Read) | (Op::Write, Op::Write)
+                )
+            }
+        }
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let rcan = Rcan::issuing_builder(
+            &service,
+            alice.verifying_key(),
+            SetCapability(vec![Op::Read, Op::Write]),
+        )
+        .sign(Expires::Never);
+
+        let witnesses = Authorizer::new(service.verifying_key()).explain_invocation(
+            alice.verifying_key(),
+            SetCapability(vec![Op::Write]),
+            &[&rcan],
+        )?;
+        assert_eq!(witnesses, [Some(Witness::new("Write permits Write"))]);
+
+        assert!(!rcan
+            .capability()
+            .permits(&SetCapability(vec![Op::Read, Op::Admin])));
+        assert!(Authorizer::new(service.verifying_key())
+            .explain_invocation(
+                alice.verifying_key(),
+                SetCapability(vec![Op::Admin]),
+                &[&rcan],
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_versioned_capability_chain() -> TestResult {
+        /// The old schema: a single level.
+        #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+        struct LevelV1(u8);
+
+        impl SchemaVersion for LevelV1 {
+            const VERSION: u8 = 1;
+        }
+
+        /// The new schema: a level, scoped to a resource.
+        #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+        struct LevelV2 {
+            level: u8,
+            resource: Option<String>,
+        }
+
+        impl SchemaVersion for LevelV2 {
+            const VERSION: u8 = 2;
+        }
+
+        impl From<LevelV1> for LevelV2 {
+            fn from(old: LevelV1) -> Self {
+                LevelV2 {
+                    level: old.0,
+                    resource: None,
+                }
+            }
+        }
+
+        impl Capability for LevelV2 {
+            fn permits(&self, other: &Self) -> bool {
+                self.level >= other.level
+                    && (self.resource.is_none() || self.resource == other.resource)
+            }
+        }
+
+        type Level = Versioned<LevelV1, LevelV2>;
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.verifying_key();
+
+        // minted before the rollout
+        let service_rcan: Rcan<Level> =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Versioned::Old(LevelV1(5)))
+                .sign(Expires::Never);
+        // minted after it
+        let alice_rcan = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            owner,
+            Versioned::New(LevelV2 {
+                level: 3,
+                resource: Some("db".into()),
+            }),
+        )
+        .sign(Expires::Never);
+
+        let chain = crate::ProofChain::new(vec![service_rcan, alice_rcan]);
+        let decoded = crate::ProofChain::<Level>::decode_verified(&chain.encode())?;
+        assert_eq!(decoded, chain);
+        assert_eq!(
+            decoded.proofs()[0].capability(),
+            &Versioned::Old(LevelV1(5))
+        );
+
+        let request = Versioned::New(LevelV2 {
+            level: 2,
+            resource: Some("db".into()),
+        });
+        let _ = Authorizer::new(owner).check_invocation_from(
+            bob.verifying_key(),
+            request,
+            &decoded.as_refs(),
+        )?;
+
+        // unknown schema versions don't decode
+        assert!(postcard::from_bytes::<Level>(&[3, 0]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_share_capability() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.verifying_key();
+        let authorizer = Authorizer::new(owner);
+        let share = |fraction_millis| ShareCapability { fraction_millis };
+
+        let root =
+            Rcan::issuing_builder(&service, alice.verifying_key(), share(300)).sign(Expires::Never);
+        let nested = Rcan::delegating_builder(&alice, bob.verifying_key(), owner, share(100))
+            .sign(Expires::Never);
+        let _ =
+            authorizer.check_invocation_from(bob.verifying_key(), share(100), &[&root, &nested])?;
+        assert!(root.links_to(&nested));
+
+        // Sub-delegating more than the parent's share doesn't verify.
+        let over = Rcan::delegating_builder(&alice, bob.verifying_key(), owner, share(500))
+            .sign(Expires::Never);
+        assert!(!root.links_to(&over));
+        assert!(authorizer
+            .check_invocation_from(bob.verifying_key(), share(500), &[&root, &over])
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_depleting_budget_capability() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let authorizer = Authorizer::new(service.verifying_key());
+        let budget = DepletingBudgetCapability;
+        let delegate = |issuer: &SigningKey, audience: &SigningKey, amount| {
+            Rcan::delegating_builder(
+                issuer,
+                audience.verifying_key(),
+                service.verifying_key(),
+                budget(amount),
+            )
+            .sign(Expires::Never)
+        };
+
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), budget(100))
+            .sign(Expires::Never);
+        let to_bob = delegate(&alice, &bob, 60);
+        let to_carol = delegate(&bob, &carol, 40);
+        let _ = authorizer.check_budget_invocation_from(
+            carol.verifying_key(),
+            40,
+            &[&root, &to_bob, &to_carol],
+        )?;
+        assert!(authorizer
+            .check_budget_invocation_from(carol.verifying_key(), 41, &[&root, &to_bob, &to_carol])
+            .is_err());
+
+        // Every link is within its parent, but together they reserve 110.
+        let greedy_carol = delegate(&bob, &carol, 50);
+        let chain = [&root, &to_bob, &greedy_carol];
+        assert!(authorizer
+            .check_invocation_from(carol.verifying_key(), budget(10), &chain)
+            .is_ok());
+        assert!(authorizer
+            .check_budget_invocation_from(carol.verifying_key(), 10, &chain)
+            .is_err());
+
+        // A second budget would be left out of the sum, yet could be spent.
+        let double_carol = Rcan::delegating_builder(
+            &bob,
+            carol.verifying_key(),
+            service.verifying_key(),
+            budget(1),
+        )
+        .add_capability(budget(60))
+        .sign(Expires::Never);
+        let chain = [&root, &to_bob, &double_carol];
+        assert!(authorizer
+            .check_invocation_from(carol.verifying_key(), budget(60), &chain)
+            .is_ok());
+        let err = authorizer
+            .check_budget_invocation_from(carol.verifying_key(), 60, &chain)
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::AmbiguousBudget { index: 2 }),
+            "{err}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_capability() {
+        let map = |entries: &[(&str, u64)]| {
+            MapCapability(
+                entries
+                    .iter()
+                    .map(|(key, epoch)| (key.to_string(), EpochCapability(*epoch)))
+                    .collect(),
+            )
+        };
+        let granted = map(&[("read", 5), ("write", 3)]);
+
+        assert!(granted.permits(&granted));
+        assert!(granted.permits(&map(&[("read", 5)])));
+        assert!(granted.permits(&map(&[("write", 1)])));
+        assert!(granted.permits(&map(&[])));
+        // added key
+        assert!(!granted.permits(&map(&[("read", 5), ("admin", 0)])));
+        // widened value
+        assert!(!granted.permits(&map(&[("write", 4)])));
+    }
+
+    #[test]
+    fn test_constrained_method_capability() -> TestResult {
+        let call = |method: &str, recipients: &[&str]| -> Result<_> {
+            Ok(ConstrainedMethodCapability {
+                method: NamespaceCapability::new(method)?,
+                args: SetCapability(
+                    recipients
+                        .iter()
+                        .map(|recipient| DomainCapability(recipient.to_string()))
+                        .collect(),
+                ),
+            })
+        };
+        let granted = call("wallet.*", &["alice.example", "bob.example"])?;
+
+        assert!(granted.permits(&call("wallet.transfer", &["alice.example"])?));
+        // the method is permitted, but the recipient isn't
+        assert!(!granted.permits(&call("wallet.transfer", &["mallory.example"])?));
+        // the recipient is permitted, but the method isn't
+        assert!(!granted.permits(&call("admin.transfer", &["alice.example"])?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tenant_scoped() {
+        let scoped = |tenant: &str, inner| TenantScoped {
+            tenant: TenantId(tenant.into()),
+            inner,
+        };
+
+        let acme_all = scoped("acme", Rpc::All);
+        assert!(acme_all.permits(&scoped("acme", Rpc::ReadWrite)));
+        assert!(!scoped("acme", Rpc::Read).permits(&scoped("acme", Rpc::ReadWrite)));
+        // Even `All` doesn't cross tenants.
+        assert!(!acme_all.permits(&scoped("globex", Rpc::Read)));
+        assert!(!acme_all.permits(&scoped("globex", Rpc::All)));
+    }
+
+    #[test]
+    fn test_rate_capability() {
+        let rate = |rate, window_secs| RateCapability { rate, window_secs };
+        let granted = rate(100, 60);
+
+        // narrower requests
+        assert!(granted.permits(&rate(100, 60)));
+        assert!(granted.permits(&rate(50, 60)));
+        assert!(granted.permits(&rate(10, 10)));
+        assert!(granted.permits(&rate(1, 1)));
+        // broader requests
+        assert!(!granted.permits(&rate(101, 60)));
+        assert!(!granted.permits(&rate(17, 10)));
+        assert!(!granted.permits(&rate(100, 1)));
+        assert!(!granted.permits(&rate(1000, 600)));
+        // malformed
+        assert!(!granted.permits(&rate(0, 0)));
+        assert!(!rate(100, 0).permits(&rate(0, 60)));
+    }
+
+    #[test]
+    fn test_policy_ref_capability() -> TestResult {
+        /// Stub policies: "reads" permits reads, "all" permits everything
+        /// and subsumes "reads".
+        struct Policies;
+
+        const READS: [u8; 32] = [1; 32];
+        const ALL: [u8; 32] = [2; 32];
+
+        impl PolicyResolver for Policies {
+            type Request = str;
+
+            fn evaluate(&self, policy_hash: &[u8; 32], request: &str) -> Result<bool, BoxError> {
+                match *policy_hash {
+                    READS => Ok(request.starts_with("read")),
+                    ALL => Ok(true),
+                    _ => Err("unknown policy".into()),
+                }
+            }
+
+            fn subsumes(&self, outer: &[u8; 32], inner: &[u8; 32]) -> Result<bool, BoxError> {
+                Ok(outer == inner || *outer == ALL)
+            }
+        }
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.verifying_key();
+        let policy = |policy_hash| PolicyRefCapability { policy_hash };
+        let auth = Authorizer::new(owner);
+
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), policy(ALL))
+            .sign(Expires::Never);
+        let reads = Rcan::delegating_builder(&alice, bob.verifying_key(), owner, policy(READS))
+            .sign(Expires::Never);
+        let chain = [&root, &reads];
+
+        let _ =
+            auth.check_policy_invocation_from(bob.verifying_key(), "read /a", &chain, &Policies)?;
+        let err = auth
+            .check_policy_invocation_from(bob.verifying_key(), "write /a", &chain, &Policies)
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::CapabilityNotPermitted { proof: 1 }),
+            "{err}"
+        );
+
+        // A narrower policy can't be delegated as a broader one.
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), policy(READS))
+            .sign(Expires::Never);
+        let all = Rcan::delegating_builder(&alice, bob.verifying_key(), owner, policy(ALL))
+            .sign(Expires::Never);
+        let err = auth
+            .check_policy_invocation_from(bob.verifying_key(), "read /a", &[&root, &all], &Policies)
+            .unwrap_err();
+        assert!(matches!(err, RcanError::BrokenLink { index: 0 }), "{err}");
+
+        // Unknown policies fail to evaluate.
+        let unknown = Rcan::issuing_builder(&service, alice.verifying_key(), policy([3; 32]))
+            .sign(Expires::Never);
+        let err = auth
+            .check_policy_invocation_from(alice.verifying_key(), "read /a", &[&unknown], &Policies)
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::PolicyEvaluation { index: 0, .. }),
+            "{err}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_leaky_bucket_capability() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let bucket = |burst, refill_per_sec| LeakyBucketCapability {
+            burst,
+            refill_per_sec,
+        };
+        let auth = Authorizer::new(service.verifying_key());
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), bucket(3, 1))
+            .sign(Expires::Never);
+        let delegation = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            bucket(2, 1),
+        )
+        .sign(Expires::Never);
+        let start = crate::SystemTime::now();
+        let at = |secs| start + crate::Duration::from_secs(secs);
+        let mut buckets = BucketStore::new();
+
+        // Bob drains his own bucket.
+        for _ in 0..2 {
+            let _ = auth.check_bucket_invocation_from_at(
+                at(0),
+                &mut buckets,
+                bob.verifying_key(),
+                &[&root, &delegation],
+            )?;
+        }
+        let err = auth
+            .check_bucket_invocation_from_at(
+                at(0),
+                &mut buckets,
+                bob.verifying_key(),
+                &[&root, &delegation],
+            )
+            .unwrap_err();
+        assert!(matches!(err, RcanError::BucketEmpty { index: 1 }), "{err}");
+
+        // Alice shares the root bucket with Bob, which has one left.
+        let _ = auth.check_bucket_invocation_from_at(
+            at(0),
+            &mut buckets,
+            alice.verifying_key(),
+            &[&root],
+        )?;
+        let err = auth
+            .check_bucket_invocation_from_at(at(0), &mut buckets, alice.verifying_key(), &[&root])
+            .unwrap_err();
+        assert!(matches!(err, RcanError::BucketEmpty { index: 0 }), "{err}");
+
+        // Both buckets refill.
+        let _ = auth.check_bucket_invocation_from_at(
+            at(1),
+            &mut buckets,
+            bob.verifying_key(),
+            &[&root, &delegation],
+        )?;
+        assert!(auth
+            .check_bucket_invocation_from_at(at(1), &mut buckets, alice.verifying_key(), &[&root])
+            .is_err());
+
+        // Buckets refill by time and hold one limit per proof.
+        let err = auth
+            .check_bucket_invocation_from_at(
+                Position::Block(1),
+                &mut buckets,
+                alice.verifying_key(),
+                &[&root],
+            )
+            .unwrap_err();
+        assert!(matches!(err, RcanError::BucketAtBlock), "{err}");
+        let two_buckets = Rcan::issuing_builder(&service, alice.verifying_key(), bucket(1, 0))
+            .add_capability(bucket(3, 1))
+            .sign(Expires::Never);
+        let err = auth
+            .check_bucket_invocation_from_at(
+                at(1),
+                &mut buckets,
+                alice.verifying_key(),
+                &[&two_buckets],
+            )
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::AmbiguousBucket { index: 0 }),
+            "{err}"
+        );
+
+        // A delegation can't widen the bucket.
+        assert!(bucket(3, 1).permits(&bucket(2, 1)));
+        assert!(!bucket(3, 1).permits(&bucket(4, 1)));
+        assert!(!bucket(3, 1).permits(&bucket(3, 2)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_access_capability() -> TestResult {
+        use AccessCapability::*;
+
+        assert!(Own.permits(&Write) && Write.permits(&Read) && Read.permits(&Read));
+        assert!(!Write.permits(&Own) && !Read.permits(&Write));
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let owner = service.verifying_key();
+        let auth = Authorizer::new(owner);
+        let delegate = |issuer, audience: &SigningKey, access| {
+            Rcan::delegating_builder(issuer, audience.verifying_key(), owner, access)
+                .sign(Expires::Never)
+        };
+
+        // Alice owns the resource and transfers ownership to Bob, who
+        // grants Carol read access.
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Own).sign(Expires::Never);
+        let transfer = delegate(&alice, &bob, Own);
+        let read = delegate(&bob, &carol, Read);
+        let _ =
+            auth.check_invocation_from(carol.verifying_key(), Read, &[&root, &transfer, &read])?;
+
+        // A writer can't pass access on.
+        let write = delegate(&alice, &bob, Write);
+        assert!(!write.links_to(&read));
+        let err = auth
+            .check_invocation_from(carol.verifying_key(), Read, &[&root, &write, &read])
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::NotDelegatable { index: 1 }),
+            "{err}"
+        );
+        let _ = auth.check_invocation_from(bob.verifying_key(), Write, &[&root, &write])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_toggles_capability() {
+        let toggles = |entries: &[(&str, bool)]| {
+            TogglesCapability(
+                entries
+                    .iter()
+                    .map(|(name, enabled)| (name.to_string(), *enabled))
+                    .collect(),
+            )
+        };
+        let grant = toggles(&[("a", true), ("b", true), ("c", false)]);
+
+        // present and true
+        assert!(grant.permits(&toggles(&[("a", true)])));
+        assert!(grant.permits(&toggles(&[("a", true), ("b", true)])));
+        // absent or explicitly off in the grant
+        assert!(!grant.permits(&toggles(&[("d", true)])));
+        assert!(!grant.permits(&toggles(&[("c", true)])));
+        assert!(!grant.permits(&toggles(&[("a", true), ("d", true)])));
+        // asking for a toggle to be off is always fine
+        assert!(grant.permits(&toggles(&[("c", false), ("d", false)])));
+        assert!(grant.permits(&toggles(&[])));
+        assert!(!toggles(&[]).permits(&toggles(&[("a", true)])));
+
+        // Disabled toggles don't carry over.
+        let other = toggles(&[("b", true), ("c", true), ("d", true)]);
+        assert_eq!(grant.meet(&other), Some(toggles(&[("b", true)])));
+        assert_eq!(
+            grant.join(&other),
+            Some(toggles(&[
+                ("a", true),
+                ("b", true),
+                ("c", true),
+                ("d", true)
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_method_name_capability() {
+        let methods = |names: &[&str]| {
+            MethodNameCapability::Methods(names.iter().map(|name| name.to_string()).collect())
+        };
+        let grant = methods(&["get", "list", "put"]);
+
+        // Set containment
+        assert!(grant.permits(&methods(&["get"])));
+        assert!(grant.permits(&methods(&["get", "put"])));
+        assert!(grant.permits(&methods(&[])));
+        assert!(grant.permits(&grant));
+        assert!(!grant.permits(&methods(&["delete"])));
+        assert!(!grant.permits(&methods(&["get", "delete"])));
+
+        // `All` permits every method, but no set permits `All`.
+        assert!(MethodNameCapability::All.permits(&grant));
+        assert!(MethodNameCapability::All.permits(&MethodNameCapability::All));
+        assert!(!grant.permits(&MethodNameCapability::All));
+
+        let other = methods(&["put", "delete"]);
+        assert_eq!(grant.meet(&other), Some(methods(&["put"])));
+        assert_eq!(
+            grant.join(&other),
+            Some(methods(&["delete", "get", "list", "put"]))
+        );
+        assert_eq!(grant.meet(&MethodNameCapability::All), Some(grant.clone()));
+        assert_eq!(
+            grant.join(&MethodNameCapability::All),
+            Some(MethodNameCapability::All)
+        );
+    }
+}
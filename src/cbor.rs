@@ -0,0 +1,120 @@
+//! CBOR encoding of [`Rcan`] tokens.
+//!
+//! The CBOR form mirrors the postcard layout, `[payload, signature]`, but
+//! emits the signature as a CBOR byte string (major type 2) instead of a
+//! 64-element array, which keeps tokens idiomatic for DAG-CBOR style
+//! pipelines. Keys already serialize as byte strings in binary formats.
+//!
+//! The signature is always over the canonical postcard preimage, so the
+//! CBOR form is only a transport encoding and decodes to the same token.
+
+use ed25519_dalek::{Signature, SIGNATURE_LENGTH};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{Payload, Rcan, RcanError, Result};
+
+/// `P` is `&Payload<C>` when encoding and `Payload<C>` when decoding.
+#[derive(Serialize, Deserialize)]
+struct CborRcan<P>(P, #[serde(with = "signature_bytes")] [u8; SIGNATURE_LENGTH]);
+
+/// Serde for signatures as a plain byte string.
+mod signature_bytes {
+    use ed25519_dalek::SIGNATURE_LENGTH;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        bytes: &[u8; SIGNATURE_LENGTH],
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(bytes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<[u8; SIGNATURE_LENGTH], D::Error> {
+        struct V;
+        impl serde::de::Visitor<'_> for V {
+            type Value = [u8; SIGNATURE_LENGTH];
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "an ed25519 signature ({} bytes)", SIGNATURE_LENGTH)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(
+                self,
+                v: &[u8],
+            ) -> core::result::Result<Self::Value, E> {
+                v.try_into().map_err(|_| E::invalid_length(v.len(), &self))
+            }
+        }
+
+        deserializer.deserialize_bytes(V)
+    }
+}
+
+impl<C> Rcan<C> {
+    /// Encodes the token as CBOR.
+    pub fn encode_cbor(&self) -> Vec<u8>
+    where
+        C: Serialize,
+    {
+        let wire = CborRcan(&self.payload, self.signature.to_bytes());
+        let mut out = Vec::new();
+        ciborium::into_writer(&wire, &mut out).expect("vec");
+        out
+    }
+
+    /// Decodes a token from its CBOR encoding and verifies its signature.
+    pub fn decode_cbor(bytes: &[u8]) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let CborRcan::<Payload<C>>(payload, signature) =
+            ciborium::from_reader(bytes).map_err(|err| RcanError::Malformed(err.to_string()))?;
+        let rcan = Rcan {
+            payload,
+            signature: Signature::from_bytes(&signature),
+        };
+        rcan.verify_signature()?;
+        Ok(rcan)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use ed25519_dalek::{SigningKey, SIGNATURE_LENGTH};
+    use testresult::TestResult;
+
+    use crate::{Expires, Rcan};
+
+    #[test]
+    fn test_cbor_roundtrip() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]);
+        let rcan =
+            Rcan::issuing_builder(&issuer, audience.verifying_key(), 7u8).sign(Expires::Never);
+
+        let cbor = rcan.encode_cbor();
+
+        // Keys are 32 byte byte strings (0x58 0x20), the signature is a
+        // 64 byte byte string (0x58 0x40) at the very end.
+        let mut issuer_bytes = vec![0x58, 0x20];
+        issuer_bytes.extend_from_slice(issuer.verifying_key().as_bytes());
+        assert!(cbor
+            .windows(issuer_bytes.len())
+            .any(|w| w == issuer_bytes.as_slice()));
+        let mut signature_bytes = vec![0x58, 0x40];
+        signature_bytes.extend_from_slice(&rcan.signature.to_bytes());
+        assert!(cbor.ends_with(&signature_bytes));
+
+        assert_eq!(Rcan::<u8>::decode_cbor(&cbor)?, rcan);
+
+        // A zeroed signature must not verify.
+        let mut forged = cbor.clone();
+        let n = forged.len();
+        forged[n - SIGNATURE_LENGTH..].fill(0);
+        assert!(Rcan::<u8>::decode_cbor(&forged).is_err());
+
+        Ok(())
+    }
+}
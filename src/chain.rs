@@ -0,0 +1,425 @@
+//! Proof chains as first-class values.
+
+use alloc::{vec, vec::Vec};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+#[cfg(feature = "std")]
+use ed25519_dalek::VerifyingKey;
+
+#[cfg(feature = "std")]
+use crate::{Authorizer, InvocationProof};
+use crate::{Capability, CapabilityOrigin, Position, Rcan, RcanError, Result, VERSION};
+#[cfg(feature = "async")]
+use crate::{ChainCursor, Expires};
+
+/// An ordered chain of delegations, starting with the token issued by the
+/// owner of the capability.
+///
+/// Serializes as the sequence of its tokens. Deserializing checks every
+/// token's signature, but not how the tokens link together, so use
+/// [`ProofChain::decode_verified`] or call [`ProofChain::validate`] on
+/// chains from untrusted sources.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "C: Serialize",
+    deserialize = "C: Deserialize<'de> + Serialize"
+))]
+pub struct ProofChain<C>(Vec<Rcan<C>>);
+
+/// Another name for [`ProofChain`], usually built with
+/// [`ProofChain::from_proofs`].
+pub type RcanChain<C> = ProofChain<C>;
+
+impl<C> ProofChain<C> {
+    /// Wraps the given proofs, root first, without validating them.
+    pub fn new(proofs: Vec<Rcan<C>>) -> Self {
+        Self(proofs)
+    }
+
+    /// Wraps the given proofs, root first, once they are
+    /// [valid](ProofChain::validate) and none of them is expired at `at`.
+    ///
+    /// Chains built this way can be stored and checked with
+    /// [`Authorizer::check_chain`] later on, which still checks expiry and
+    /// who the chain is for, since those depend on the invocation.
+    pub fn from_proofs(proofs: Vec<Rcan<C>>, at: impl Into<Position>) -> Result<Self>
+    where
+        C: Capability,
+    {
+        let at = at.into();
+        let chain = Self(proofs);
+        chain.validate()?;
+        if let Some((index, expired)) = chain
+            .0
+            .iter()
+            .enumerate()
+            .find(|(_, proof)| !proof.expires().is_valid_at(at))
+        {
+            return Err(RcanError::Expired {
+                index,
+                valid_until: expired.expires().clone(),
+            });
+        }
+        Ok(chain)
+    }
+
+    /// The proofs in the chain, root first.
+    pub fn proofs(&self) -> &[Rcan<C>] {
+        &self.0
+    }
+
+    /// The proofs as references, in the shape the [`crate::Authorizer`]
+    /// checks expect.
+    pub fn as_refs(&self) -> Vec<&Rcan<C>> {
+        self.0.iter().collect()
+    }
+
+    /// Consumes the chain, returning its proofs.
+    pub fn into_proofs(self) -> Vec<Rcan<C>> {
+        self.0
+    }
+
+    /// Checks that the chain is well-formed on its own: it isn't empty,
+    /// the root is issued by the capability's owner, and each proof
+    /// [links to](Rcan::links_to) the next.
+    ///
+    /// This doesn't check expiry or who the chain is for; that's up to the
+    /// [`crate::Authorizer`].
+    pub fn validate(&self) -> Result<()>
+    where
+        C: Capability,
+    {
+        let Some(root) = self.0.first() else {
+            return Err(RcanError::EmptyChain);
+        };
+        if root.capability_origin() != &CapabilityOrigin::Issuer {
+            return Err(RcanError::RootIsDelegation {
+                owner: root.capability_issuer().as_bytes().to_vec(),
+            });
+        }
+        if let Some(i) = self
+            .0
+            .windows(2)
+            .position(|pair| !pair[0].links_to(&pair[1]))
+        {
+            return Err(RcanError::BrokenLink { index: i });
+        }
+        Ok(())
+    }
+
+    /// Encodes the chain, prefixed with the wire format [`VERSION`].
+    pub fn encode(&self) -> Vec<u8>
+    where
+        C: Serialize,
+    {
+        postcard::to_extend(self, vec![VERSION]).expect("vec")
+    }
+
+    /// Decodes a chain, verifying every token's signature and then
+    /// [validating](ProofChain::validate) the chain.
+    pub fn decode_verified(bytes: &[u8]) -> Result<Self>
+    where
+        C: Capability + DeserializeOwned,
+    {
+        let Some(version) = bytes.first() else {
+            return Err(RcanError::EmptyToken);
+        };
+        if *version != VERSION {
+            return Err(RcanError::UnsupportedVersion(*version));
+        }
+        let chain: Self = postcard::from_bytes(&bytes[1..])?;
+        chain.validate()?;
+        Ok(chain)
+    }
+
+    /// Verifies an invocation like [`Authorizer::check_invocation_from`],
+    /// with the proof chain arriving as a stream, root first.
+    ///
+    /// Each proof is checked as soon as it arrives and only the latest one
+    /// is kept, so long chains don't have to be buffered. Fails on the
+    /// first bad proof or stream error, without polling further. Stream
+    /// errors are reported as [`RcanError::Receive`].
+    #[cfg(feature = "async")]
+    pub async fn verify_stream<E>(
+        authorizer: &Authorizer,
+        invoker: VerifyingKey,
+        capability: C,
+        stream: impl n0_future::Stream<Item = Result<Rcan<C>, E>>,
+    ) -> Result<InvocationProof>
+    where
+        C: Capability,
+        E: Into<crate::BoxError>,
+    {
+        use n0_future::StreamExt;
+
+        let now = crate::SystemTime::now().into();
+        let mut cursor = ChainCursor::new(authorizer);
+        let mut parent: Option<Rcan<C>> = None;
+        let mut expires = Expires::Never;
+        let mut stream = core::pin::pin!(stream);
+        while let Some(proof) = stream.next().await {
+            let i = cursor.index;
+            let proof = proof.map_err(|err| RcanError::Receive {
+                index: i,
+                source: err.into(),
+            })?;
+            authorizer.check_next_proof(now, &mut cursor, parent.as_ref(), &proof)?;
+            if !proof.permits(&capability) {
+                return Err(RcanError::CapabilityNotPermitted { proof: i });
+            }
+            expires = Expires::earliest([expires, proof.expires().clone()]);
+            parent = Some(proof);
+        }
+        cursor.check_end(invoker, parent.as_ref())?;
+
+        Ok(InvocationProof { expires })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C> ProofChain<C> {
+    /// Whether the root of the chain is issued by one of `allowed_roots`.
+    ///
+    /// Only looks at the root, see [`ProofChain::verify_for`] to check the
+    /// whole chain.
+    pub fn root_in(&self, allowed_roots: &HashSet<VerifyingKey>) -> bool {
+        self.0
+            .first()
+            .is_some_and(|root| allowed_roots.contains(root.issuer()))
+    }
+
+    /// Verifies an invocation through the chain, for a verifier trusting
+    /// every key in `allowed_roots` as an owner of capabilities.
+    ///
+    /// Checks the chain like [`Authorizer::check_chain`] with a default
+    /// [`Authorizer`] for its root, once the root is issued by one of
+    /// `allowed_roots`. Chains rooted on another key fail with
+    /// [`RcanError::UntrustedIssuer`].
+    pub fn verify_for(
+        &self,
+        allowed_roots: &HashSet<VerifyingKey>,
+        invoker: VerifyingKey,
+        capability: C,
+    ) -> Result<InvocationProof>
+    where
+        C: Capability,
+    {
+        let Some(root) = self.0.first() else {
+            return Err(RcanError::EmptyChain);
+        };
+        if !self.root_in(allowed_roots) {
+            return Err(RcanError::UntrustedIssuer(
+                root.issuer().as_bytes().to_vec(),
+            ));
+        }
+        Authorizer::new(*root.issuer()).check_chain(invoker, capability, self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Authorizer {
+    /// Verifies an invocation like [`Authorizer::check_invocation_from`],
+    /// through a chain from [`ProofChain::from_proofs`].
+    pub fn check_chain<C: Capability>(
+        &self,
+        invoker: VerifyingKey,
+        capability: C,
+        chain: &RcanChain<C>,
+    ) -> Result<InvocationProof> {
+        self.check_invocation_from(invoker, capability, &chain.as_refs())
+    }
+}
+
+impl<C> From<Vec<Rcan<C>>> for ProofChain<C> {
+    fn from(proofs: Vec<Rcan<C>>) -> Self {
+        Self::new(proofs)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use ed25519_dalek::SigningKey;
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::{caps::EpochCapability, Expires, SystemTime};
+
+    #[test]
+    fn test_rcan_chain_from_proofs() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.verifying_key();
+        let now = SystemTime::now();
+
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), EpochCapability(5))
+            .sign(Expires::valid_for_secs(60));
+        let delegation =
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, EpochCapability(3))
+                .sign(Expires::Never);
+
+        let chain = RcanChain::from_proofs(vec![root.clone(), delegation.clone()], now)?;
+        let _ =
+            Authorizer::new(owner).check_chain(bob.verifying_key(), EpochCapability(2), &chain)?;
+
+        // Out of order, so alice's token isn't addressed to bob's issuer.
+        assert!(RcanChain::from_proofs(vec![delegation.clone(), root.clone()], now).is_err());
+        // The root has expired by then.
+        let later = now + crate::Duration::from_secs(120);
+        assert!(matches!(
+            RcanChain::from_proofs(vec![root, delegation], later),
+            Err(RcanError::Expired { index: 0, .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_proof_chain_roundtrip() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.verifying_key();
+
+        let chain = ProofChain::new(vec![
+            Rcan::issuing_builder(&service, alice.verifying_key(), EpochCapability(5))
+                .sign(Expires::Never),
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, EpochCapability(3))
+                .sign(Expires::Never),
+        ]);
+        chain.validate()?;
+
+        let decoded = ProofChain::<EpochCapability>::decode_verified(&chain.encode())?;
+        assert_eq!(decoded, chain);
+        let _ = Authorizer::new(owner).check_invocation_from(
+            bob.verifying_key(),
+            EpochCapability(2),
+            &decoded.as_refs(),
+        )?;
+
+        // Out of order chains don't validate.
+        let mut proofs = chain.into_proofs();
+        proofs.reverse();
+        let reversed = ProofChain::new(proofs);
+        assert!(reversed.validate().is_err());
+        assert!(ProofChain::<EpochCapability>::decode_verified(&reversed.encode()).is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_verify_stream() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let owner = service.verifying_key();
+        let authorizer = Authorizer::new(owner);
+
+        let proofs = vec![
+            Rcan::issuing_builder(&service, alice.verifying_key(), EpochCapability(5))
+                .sign(Expires::Never),
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, EpochCapability(4))
+                .sign(Expires::At(u64::MAX)),
+            Rcan::delegating_builder(&bob, carol.verifying_key(), owner, EpochCapability(3))
+                .sign(Expires::Never),
+        ];
+        let stream = |proofs: Vec<Rcan<EpochCapability>>| {
+            n0_future::stream::iter(proofs.into_iter().map(Ok::<_, std::io::Error>))
+        };
+
+        n0_future::future::block_on(async {
+            let proof = ProofChain::verify_stream(
+                &authorizer,
+                carol.verifying_key(),
+                EpochCapability(3),
+                stream(proofs.clone()),
+            )
+            .await?;
+            assert_eq!(proof.expires(), &Expires::At(u64::MAX));
+
+            // Too broad for the last link.
+            assert!(ProofChain::verify_stream(
+                &authorizer,
+                carol.verifying_key(),
+                EpochCapability(4),
+                stream(proofs.clone()),
+            )
+            .await
+            .is_err());
+
+            // Errors from the stream fail the verification.
+            let broken = n0_future::stream::iter([
+                Ok(proofs[0].clone()),
+                Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset)),
+            ]);
+            let err = ProofChain::verify_stream(
+                &authorizer,
+                alice.verifying_key(),
+                EpochCapability(1),
+                broken,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(err, RcanError::Receive { index: 1, .. }), "{err}");
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_verify_for_allowed_roots() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let other = SigningKey::from_bytes(&[3u8; 32]);
+        let chain_from = |owner: &SigningKey| {
+            let root = Rcan::issuing_builder(owner, alice.verifying_key(), EpochCapability(5))
+                .sign(Expires::Never);
+            let delegation = Rcan::delegating_builder(
+                &alice,
+                bob.verifying_key(),
+                owner.verifying_key(),
+                EpochCapability(3),
+            )
+            .sign(Expires::Never);
+            ProofChain::new(vec![root, delegation])
+        };
+        let allowed_roots = HashSet::from([service.verifying_key(), other.verifying_key()]);
+
+        for owner in [&service, &other] {
+            let chain = chain_from(owner);
+            assert!(chain.root_in(&allowed_roots));
+            let _ = chain.verify_for(&allowed_roots, bob.verifying_key(), EpochCapability(2))?;
+        }
+
+        // Rooted on a key that isn't listed.
+        let unlisted = chain_from(&alice);
+        assert!(!unlisted.root_in(&allowed_roots));
+        let err = unlisted
+            .verify_for(&allowed_roots, bob.verifying_key(), EpochCapability(2))
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::UntrustedIssuer(ref key) if key == alice.verifying_key().as_bytes()),
+            "{err}"
+        );
+
+        // A listed root still needs a valid chain.
+        let chain = chain_from(&service);
+        let err = chain
+            .verify_for(&allowed_roots, bob.verifying_key(), EpochCapability(4))
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::CapabilityNotPermitted { .. }),
+            "{err}"
+        );
+        assert!(!ProofChain::<EpochCapability>::new(vec![]).root_in(&allowed_roots));
+
+        Ok(())
+    }
+}
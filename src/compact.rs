@@ -0,0 +1,140 @@
+//! Compact, JWT-shaped string encoding of [`Rcan`] tokens.
+//!
+//! A compact token is `header.payload.signature`, three base64url segments
+//! without padding, for infrastructure that expects JWT-style strings:
+//!
+//! - `header` is the postcard encoded [`Header`], the wire [`VERSION`] and
+//!   the signature algorithm,
+//! - `payload` is the postcard encoded payload,
+//! - `signature` is the raw 64 byte ed25519 signature.
+//!
+//! Unlike a JWT, the signature doesn't cover the header segment. It is
+//! always over the canonical postcard preimage, so the compact form is only
+//! a transport encoding and decodes to the same token. The header is
+//! checked on decoding instead.
+
+use alloc::{format, string::String, vec::Vec};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ed25519_dalek::Signature;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{Payload, Rcan, RcanError, Result, VERSION};
+
+/// The algorithm name in the header, as registered for JWS.
+const ALG: &str = "EdDSA";
+
+/// The first segment of a compact token.
+#[derive(Serialize, Deserialize)]
+struct Header<'a> {
+    version: u8,
+    alg: &'a str,
+}
+
+impl<C> Rcan<C> {
+    /// Encodes the token as a compact `header.payload.signature` string.
+    pub fn to_compact(&self) -> String
+    where
+        C: Serialize,
+    {
+        let header = postcard::to_allocvec(&Header {
+            version: VERSION,
+            alg: ALG,
+        })
+        .expect("vec");
+        let payload = postcard::to_allocvec(&self.payload).expect("vec");
+        format!(
+            "{}.{}.{}",
+            URL_SAFE_NO_PAD.encode(header),
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(self.signature.to_bytes())
+        )
+    }
+
+    /// Decodes a token from its compact encoding and verifies its
+    /// signature.
+    pub fn from_compact(compact: &str) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let segments: Vec<&str> = compact.split('.').collect();
+        let [header, payload, signature] = segments[..] else {
+            return Err(RcanError::Malformed(format!(
+                "expected 3 segments, got {}",
+                segments.len()
+            )));
+        };
+
+        let header = decode_segment("header", header)?;
+        let header: Header = postcard::from_bytes(&header)?;
+        if header.version != VERSION {
+            return Err(RcanError::UnsupportedVersion(header.version));
+        }
+        if header.alg != ALG {
+            return Err(RcanError::Malformed(format!(
+                "unsupported algorithm: {}",
+                header.alg
+            )));
+        }
+
+        let payload = decode_segment("payload", payload)?;
+        let (payload, rest) = postcard::take_from_bytes::<Payload<C>>(&payload)?;
+        if !rest.is_empty() {
+            return Err(RcanError::Malformed("trailing payload bytes".into()));
+        }
+
+        let signature = Signature::from_slice(&decode_segment("signature", signature)?)?;
+
+        let rcan = Rcan { payload, signature };
+        rcan.verify_signature()?;
+        Ok(rcan)
+    }
+}
+
+/// Decodes the base64url segment `name`.
+fn decode_segment(name: &str, segment: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|err| RcanError::Malformed(format!("decoding {name}: {err}")))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use ed25519_dalek::SigningKey;
+    use testresult::TestResult;
+
+    use crate::{Expires, Rcan};
+
+    #[test]
+    fn test_compact_roundtrip() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]);
+        let rcan =
+            Rcan::issuing_builder(&issuer, audience.verifying_key(), 7u8).sign(Expires::Never);
+
+        let compact = rcan.to_compact();
+        assert_eq!(compact.split('.').count(), 3);
+        assert!(!compact.contains(['=', '+', '/']));
+        assert_eq!(Rcan::<u8>::from_compact(&compact)?, rcan);
+
+        // Changing the capability in the middle segment breaks the signature.
+        let segments: Vec<&str> = compact.split('.').collect();
+        let mut payload = URL_SAFE_NO_PAD.decode(segments[1])?;
+        // the length prefixed issuer key, the list of one length prefixed
+        // audience key, the origin tag and the number of capabilities
+        assert_eq!(payload[69], 7);
+        payload[69] = 8;
+        let tampered = format!(
+            "{}.{}.{}",
+            segments[0],
+            URL_SAFE_NO_PAD.encode(&payload),
+            segments[2]
+        );
+        assert!(Rcan::<u8>::from_compact(&tampered).is_err());
+
+        assert!(Rcan::<u8>::from_compact(&compact[..compact.rfind('.').unwrap()]).is_err());
+
+        Ok(())
+    }
+}
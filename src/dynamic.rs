@@ -0,0 +1,210 @@
+//! Capabilities whose type is only known at runtime.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::any::Any;
+
+use ed25519_dalek::VerifyingKey;
+use serde::{ser::SerializeTuple, Deserialize, Serialize};
+
+use crate::{
+    Capability, CapabilityOrigin, Ed25519, Expires, Meta, NotBefore, Payload, Rcan, RcanError,
+    Result, SignatureWire, VERSION,
+};
+
+/// An object safe [`Capability`], for gateways that can't name the
+/// capability type at compile time.
+///
+/// Implemented for every `'static` [`Capability`] that is [`Debug`]. Use
+/// `Box<dyn DynCapability>` as the capability type, and decode tokens with
+/// [`Rcan::decode_dyn`].
+///
+/// [`Debug`]: core::fmt::Debug
+pub trait DynCapability: Any + core::fmt::Debug {
+    /// Like [`Capability::permits`]. Capabilities of different types never
+    /// permit each other.
+    fn permits_dyn(&self, other: &dyn DynCapability) -> bool;
+
+    /// The capability as [`Any`], to downcast it to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// The postcard encoding of the capability, as it is signed.
+    fn to_postcard(&self) -> Vec<u8>;
+}
+
+impl<C: Capability + core::fmt::Debug + 'static> DynCapability for C {
+    fn permits_dyn(&self, other: &dyn DynCapability) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<C>()
+            .is_some_and(|other| self.permits(other))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn to_postcard(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("vec")
+    }
+}
+
+/// Serializes as the capability's postcard bytes, as a tuple of bytes. In
+/// postcard that is exactly the encoding of the concrete capability, so
+/// signatures stay valid.
+impl Serialize for dyn DynCapability {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        let bytes = self.to_postcard();
+        let mut tuple = serializer.serialize_tuple(bytes.len())?;
+        for byte in &bytes {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+}
+
+/// The payload fields in front of the capabilities.
+#[derive(Deserialize)]
+struct PayloadHead {
+    #[serde(deserialize_with = "crate::verifying_key_serde::deserialize::<Ed25519, _>")]
+    issuer: VerifyingKey,
+    #[serde(deserialize_with = "crate::deserialize_audiences::<Ed25519, _>")]
+    audiences: Vec<VerifyingKey>,
+    capability_origin: CapabilityOrigin,
+}
+
+/// The payload fields after the capabilities.
+#[derive(Deserialize)]
+struct PayloadTail {
+    valid_until: Expires,
+    valid_from: NotBefore,
+    issued_at: u64,
+    max_delegation_depth: Option<u8>,
+    meta: Meta,
+    #[serde(deserialize_with = "crate::verifying_keys_serde::deserialize::<Ed25519, _>")]
+    audience_members: Vec<VerifyingKey>,
+    #[serde(deserialize_with = "crate::optional_verifying_keys_serde::deserialize::<Ed25519, _>")]
+    delegation_audience_allowlist: Option<Vec<VerifyingKey>>,
+}
+
+impl Rcan<Box<dyn DynCapability>> {
+    /// Decodes a token with capabilities of a type unknown at compile
+    /// time, and verifies its signature.
+    ///
+    /// `decode_cap` is called with the encoded token from the start of
+    /// each capability on, and decodes it, ignoring any bytes after it.
+    /// Everything else is decoded like in [`Rcan::decode`].
+    pub fn decode_dyn(
+        bytes: &[u8],
+        decode_cap: impl Fn(&[u8]) -> Result<Box<dyn DynCapability>>,
+    ) -> Result<Self> {
+        let Some((version, rest)) = bytes.split_first() else {
+            return Err(RcanError::EmptyToken);
+        };
+        if *version != VERSION {
+            return Err(RcanError::UnsupportedVersion(*version));
+        }
+
+        let (head, mut rest) = postcard::take_from_bytes::<PayloadHead>(rest)?;
+        let (count, after_count) = postcard::take_from_bytes::<usize>(rest)?;
+        rest = after_count;
+        if count == 0 {
+            return Err(RcanError::Malformed("token without capabilities".into()));
+        }
+        let mut capabilities = Vec::new();
+        for _ in 0..count {
+            let capability = decode_cap(rest)?;
+            // The capability's own encoding tells how far it extends.
+            let encoded = capability.to_postcard();
+            let Some(after) = rest.strip_prefix(encoded.as_slice()) else {
+                return Err(RcanError::Malformed(
+                    "capability doesn't re-encode to its bytes".into(),
+                ));
+            };
+            capabilities.push(capability);
+            rest = after;
+        }
+        let (tail, rest) = postcard::take_from_bytes::<PayloadTail>(rest)?;
+        let SignatureWire(signature) = postcard::from_bytes::<SignatureWire>(rest)?;
+
+        let rcan = Rcan {
+            payload: Payload {
+                issuer: head.issuer,
+                audiences: head.audiences,
+                capability_origin: head.capability_origin,
+                capabilities,
+                valid_until: tail.valid_until,
+                valid_from: tail.valid_from,
+                issued_at: tail.issued_at,
+                max_delegation_depth: tail.max_delegation_depth,
+                meta: tail.meta,
+                audience_members: tail.audience_members,
+                delegation_audience_allowlist: tail.delegation_audience_allowlist,
+            },
+            signature,
+        };
+        rcan.verify_signature()?;
+        Ok(rcan)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use ed25519_dalek::SigningKey;
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::{
+        caps::{DomainCapability, EpochCapability},
+        test::Rpc,
+    };
+
+    #[test]
+    fn test_decode_dyn() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::ReadWrite)
+            .add_capability(Rpc::Read)
+            .with_meta("request", "1")
+            .sign(Expires::Never);
+        let encoded = rcan.encode();
+
+        let decode_rpc = |bytes: &[u8]| -> Result<Box<dyn DynCapability>> {
+            let (rpc, _) = postcard::take_from_bytes::<Rpc>(bytes)?;
+            Ok(Box::new(rpc))
+        };
+        let decoded = Rcan::decode_dyn(&encoded, decode_rpc)?;
+        assert_eq!(decoded.capabilities().len(), 2);
+        assert_eq!(decoded.meta(), rcan.meta());
+        assert_eq!(decoded.encode(), encoded);
+
+        let read: Box<dyn DynCapability> = Box::new(Rpc::Read);
+        let all: Box<dyn DynCapability> = Box::new(Rpc::All);
+        assert!(decoded.capability().permits_dyn(&*read));
+        assert!(!decoded.capability().permits_dyn(&*all));
+        // Different capability types never permit each other.
+        let epoch: Box<dyn DynCapability> = Box::new(EpochCapability(1));
+        assert!(!decoded.capability().permits_dyn(&*epoch));
+        assert_eq!(
+            decoded.capability().as_any().downcast_ref::<Rpc>(),
+            Some(&Rpc::ReadWrite)
+        );
+
+        // A decoder for the wrong type either fails or breaks the signature.
+        let decode_domain = |bytes: &[u8]| -> Result<Box<dyn DynCapability>> {
+            let (domain, _) = postcard::take_from_bytes::<DomainCapability>(bytes)?;
+            Ok(Box::new(domain))
+        };
+        assert!(Rcan::decode_dyn(&encoded, decode_domain).is_err());
+
+        // Tampering is still detected.
+        let mut forged = encoded.clone();
+        let n = forged.len();
+        forged[n - 1] ^= 1;
+        assert!(Rcan::decode_dyn(&forged, decode_rpc).is_err());
+
+        Ok(())
+    }
+}
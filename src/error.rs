@@ -0,0 +1,248 @@
+//! The error type of this crate.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::{Duration, Expires, NotBefore};
+
+/// An error from a user-provided hook, such as
+/// [`Capability::try_permits`](crate::Capability::try_permits).
+pub type BoxError = Box<dyn core::error::Error + Send + Sync>;
+
+/// The bytes of a [`VerifyingKey`](crate::VerifyingKey), or a key of
+/// another [`SignatureScheme`](crate::SignatureScheme), as reported in
+/// errors.
+pub type KeyBytes = Vec<u8>;
+
+/// Everything that can go wrong decoding, verifying or invoking tokens.
+///
+/// Keys are reported as their [`KeyBytes`], which keeps the error small.
+///
+/// Failures of [`Capability::permits`](crate::Capability::permits) are
+/// reported as [`RcanError::CapabilityNotPermitted`] or
+/// [`RcanError::RequestNotPermitted`], so callers can tell a capability
+/// that is too weak apart from a chain that is broken.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum RcanError {
+    /// There were no bytes to decode.
+    #[error("cannot decode, token is empty")]
+    EmptyToken,
+    /// The wire format version isn't [`VERSION`](crate::VERSION).
+    #[error("invalid version: {0}")]
+    UnsupportedVersion(u8),
+    /// The postcard encoding is invalid.
+    #[error("decoding failed")]
+    Decode(#[from] postcard::Error),
+    /// Reading the token from a stream failed, see
+    /// [`Rcan::decode_from`](crate::Rcan::decode_from).
+    #[cfg(feature = "std")]
+    #[error("reading token failed")]
+    Io(#[source] std::io::Error),
+    /// Some other encoding is invalid, e.g. of the compact or CBOR form.
+    #[error("malformed token: {0}")]
+    Malformed(String),
+    /// The token decoded, but isn't in its canonical form, see
+    /// [`Rcan::decode_strict`](crate::Rcan::decode_strict).
+    #[error("non-canonical token encoding")]
+    NonCanonical,
+    /// A signature doesn't verify.
+    #[error("invalid signature")]
+    InvalidSignature(#[from] ed25519_dalek::SignatureError),
+    /// A signature in a batch doesn't verify, see
+    /// [`Rcan::verify_batch`](crate::Rcan::verify_batch).
+    #[error("invalid signature of token #{index} in batch")]
+    InvalidBatchSignature {
+        /// Position of the first bad token in the batch.
+        index: usize,
+        #[source]
+        source: ed25519_dalek::SignatureError,
+    },
+    /// A token was issued by someone else than required.
+    #[error(
+        "issuer mismatch: expected {}, got {}",
+        hex::encode(.expected),
+        hex::encode(.found)
+    )]
+    IssuerMismatch { expected: KeyBytes, found: KeyBytes },
+    /// A proof in the chain isn't issued by the audience of the proof
+    /// before it, or by the authorizer for the first proof.
+    #[error(
+        "invocation failed: proof #{index} is issued by {}, expected {}",
+        hex::encode(.found),
+        hex::encode(.expected)
+    )]
+    ProofIssuerMismatch {
+        index: usize,
+        expected: KeyBytes,
+        found: KeyBytes,
+    },
+    /// The token's issuer isn't trusted, see
+    /// [`Rcan::verify_resolved`](crate::Rcan::verify_resolved) and
+    /// [`ProofChain::verify_for`](crate::ProofChain::verify_for).
+    #[error("untrusted issuer: {}", hex::encode(.0))]
+    UntrustedIssuer(KeyBytes),
+    /// The token isn't held by this key, see
+    /// [`Rcan::confirm_holder`](crate::Rcan::confirm_holder).
+    #[error("token is not held by {}", hex::encode(.0))]
+    NotHolder(KeyBytes),
+    /// A proof in the chain has expired.
+    #[error("invocation failed: proof #{index} expired at {valid_until}")]
+    Expired {
+        /// Position of the proof in the chain, 0 for a single token.
+        index: usize,
+        /// When the proof expired, renew it to continue.
+        valid_until: Expires,
+    },
+    /// A delegation would outlive its parent, see
+    /// [`RcanBuilder::sign_attenuated`](crate::RcanBuilder::sign_attenuated).
+    #[error("delegation expires at {valid_until}, after its parent at {parent}")]
+    ExpiresAfterParent {
+        valid_until: Expires,
+        parent: Expires,
+    },
+    /// A delegation can't follow its parent, see
+    /// [`RcanBuilder::sign_attenuated`](crate::RcanBuilder::sign_attenuated).
+    #[error("delegation not permitted: {0}")]
+    DelegationNotPermitted(&'static str),
+    /// A proof in the chain isn't valid yet.
+    #[error("invocation failed: proof #{index} is not valid before {valid_from}")]
+    NotYetValid {
+        /// Position of the proof in the chain, 0 for a single token.
+        index: usize,
+        /// When the proof becomes valid, wait until then.
+        valid_from: NotBefore,
+    },
+    /// A proof was issued before the
+    /// [`Authorizer::with_issuance_floor`](crate::Authorizer::with_issuance_floor).
+    #[error("invocation failed: proof #{index} was issued at {issued_at}, before the issuance floor {floor}")]
+    IssuedBeforeFloor {
+        /// Position of the proof in the chain.
+        index: usize,
+        /// When the proof was issued, in unix seconds.
+        issued_at: u64,
+        /// The floor, in unix seconds.
+        floor: u64,
+    },
+    /// A proof doesn't delegate a capability of the authorizer.
+    #[error(
+        "invocation failed: proof #{index} is missing delegation for capability of {}",
+        hex::encode(.owner)
+    )]
+    MissingDelegation { index: usize, owner: KeyBytes },
+    /// A proof is re-delegated more often than it allows.
+    #[error("invocation failed: proof #{index} allows at most {depth} further delegations")]
+    DelegationDepthExceeded { index: usize, depth: u8 },
+    /// A proof is re-delegated although one of its capabilities doesn't
+    /// allow it, see [`Capability::may_delegate`](crate::Capability::may_delegate).
+    #[error("invocation failed: proof #{index} doesn't allow re-delegation")]
+    NotDelegatable { index: usize },
+    /// A proof is delegated to an audience its parent doesn't allow.
+    #[error(
+        "invocation failed: proof #{index} is delegated to {}, which is not in the allow-list of its parent",
+        hex::encode(.audience)
+    )]
+    AudienceNotAllowed { index: usize, audience: KeyBytes },
+    /// A proof expires after the proof before it, see
+    /// [`Authorizer::with_monotonic_expiry`](crate::Authorizer::with_monotonic_expiry).
+    #[error("invocation failed: proof #{index} expires after its parent")]
+    OutlivesParent { index: usize },
+    /// The chain doesn't end in the invoker.
+    #[error(
+        "invocation failed: expected delegation chain to end in {}, but the invocation is authenticated by {}",
+        hex::encode(.expected),
+        hex::encode(.found)
+    )]
+    ChainEndpointMismatch { expected: KeyBytes, found: KeyBytes },
+    /// The chain doesn't end in any of the candidate invokers, see
+    /// [`Authorizer::check_invocation_from_any_invoker`](crate::Authorizer::check_invocation_from_any_invoker).
+    #[error(
+        "invocation failed: expected delegation chain to end in {}, which isn't among the invokers",
+        hex::encode(.expected)
+    )]
+    NoMatchingInvoker { expected: KeyBytes },
+    /// The proof chain has more proofs than the
+    /// [`Authorizer::with_max_depth`](crate::Authorizer::with_max_depth).
+    #[error("invocation failed: proof chain is longer than {max_depth} proofs")]
+    ChainTooLong { max_depth: usize },
+    /// The proof chain is valid for longer than the
+    /// [`Authorizer::with_max_window`](crate::Authorizer::with_max_window).
+    #[error("invocation failed: proof chain is valid for longer than {max_window:?}")]
+    ValidityWindowExceeded { max_window: Duration },
+    /// The proof chain is empty where it can't be.
+    #[error("invalid proof chain: empty")]
+    EmptyChain,
+    /// The root of a chain doesn't issue its own capability.
+    #[error(
+        "invalid proof chain: root proof is a delegation of {}",
+        hex::encode(.owner)
+    )]
+    RootIsDelegation { owner: KeyBytes },
+    /// A proof doesn't [link to](crate::Rcan::links_to) the next one.
+    #[error("invalid proof chain: proof #{index} doesn't link to the next proof")]
+    BrokenLink { index: usize },
+    /// The capability is not permitted by the proof at `proof`.
+    #[error("invocation failed: capability not permitted by proof #{proof}")]
+    CapabilityNotPermitted { proof: usize },
+    /// One of several requested capabilities, `requested[request]`, is not
+    /// permitted by the proof at `proof`.
+    #[error("invocation failed: requested capability #{request} not permitted by proof #{proof}")]
+    RequestNotPermitted { request: usize, proof: usize },
+    /// The capability is not permitted by the cross-signing token of a
+    /// [`ReanchoredChain`](crate::ReanchoredChain).
+    #[error("invocation failed: capability not permitted by cross-signing token")]
+    CrossSignNotPermitted,
+    /// The delegations of a budget reserve more than the root budget.
+    #[error("invocation failed: delegations reserve more than the root budget of {budget}")]
+    BudgetExceeded { budget: u64 },
+    /// A [`DepletingBudgetCapability`](crate::caps::DepletingBudgetCapability)
+    /// proof holds more than one budget.
+    #[error("invocation failed: proof #{index} holds more than one budget")]
+    AmbiguousBudget { index: usize },
+    /// The bucket of a [`LeakyBucketCapability`](crate::caps::LeakyBucketCapability)
+    /// proof is empty.
+    #[error("invocation failed: rate limit of proof #{index} exhausted")]
+    BucketEmpty { index: usize },
+    /// A [`LeakyBucketCapability`](crate::caps::LeakyBucketCapability)
+    /// proof holds more than one bucket.
+    #[error("invocation failed: proof #{index} holds more than one rate limit")]
+    AmbiguousBucket { index: usize },
+    /// Rate limits were checked at a block height, but buckets refill by
+    /// wall-clock time.
+    #[error("invocation failed: rate limits can't be checked at a block height")]
+    BucketAtBlock,
+    /// A proof has been revoked.
+    #[error("invocation failed: proof #{index} has been revoked")]
+    Revoked { index: usize },
+    /// Looking up whether a proof has been revoked failed.
+    #[error("invocation failed: revocation lookup for proof #{index}")]
+    RevocationLookup {
+        index: usize,
+        #[source]
+        source: BoxError,
+    },
+    /// An [`AsyncSigner`](crate::AsyncSigner) failed to sign a token.
+    #[error("signing failed")]
+    Signing(#[source] BoxError),
+    /// A [`PolicyResolver`](crate::caps::PolicyResolver) failed to
+    /// evaluate the policy of a proof.
+    #[error("invocation failed: evaluating the policy of proof #{index}")]
+    PolicyEvaluation {
+        index: usize,
+        #[source]
+        source: BoxError,
+    },
+    /// Receiving a proof of a streamed chain failed.
+    #[error("invocation failed: receiving proof #{index}")]
+    Receive {
+        index: usize,
+        #[source]
+        source: BoxError,
+    },
+    /// There is nothing to intersect, see
+    /// [`Authorizer::effective_capability_intersection`](crate::Authorizer::effective_capability_intersection).
+    #[error("both proof chains are empty, there is no capability to intersect")]
+    NothingToIntersect,
+    /// A capability value is malformed.
+    #[error("invalid capability: {0}")]
+    InvalidCapability(String),
+}
@@ -0,0 +1,184 @@
+//! Encoding of [`Rcan`] tokens to and decoding them from [`std::io`]
+//! streams, e.g. files and sockets.
+//!
+//! The bytes are exactly those of [`Rcan::encode`], written and read
+//! incrementally instead of through an intermediate buffer. Decoding
+//! reads no further than the end of the token, so tokens can be read one
+//! after another from the same stream.
+
+use std::io::{self, Read, Write};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Rcan, RcanError, Result, VERSION};
+
+/// The size of the buffer [`Rcan::decode_from`] reads string and byte
+/// fields into.
+const SCRATCH_LEN: usize = 16 * 1024;
+
+impl<C> Rcan<C> {
+    /// Writes the token to `writer`, in the encoding of [`Rcan::encode`].
+    ///
+    /// The token is serialized straight into `writer`, wrap unbuffered
+    /// writers in a [`std::io::BufWriter`].
+    pub fn encode_to<W: Write>(&self, writer: &mut W) -> io::Result<()>
+    where
+        C: Serialize,
+    {
+        writer.write_all(&[VERSION])?;
+        let mut writer = Tracked::new(writer);
+        match postcard::to_io(self, &mut writer) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(writer.error.take().unwrap_or_else(|| io::Error::other(err))),
+        }
+    }
+
+    /// Reads a token from `reader` and verifies its signature, like
+    /// [`Rcan::decode`] does for a slice.
+    ///
+    /// Reads no further than the end of the token. String and byte fields
+    /// are read through a 16 KiB buffer, tokens with longer fields fail
+    /// with [`RcanError::Decode`], decode those with [`Rcan::decode`].
+    /// Failures of `reader` are reported as [`RcanError::Io`], a stream
+    /// that ends before the version byte as [`RcanError::EmptyToken`].
+    pub fn decode_from<R: Read>(reader: &mut R) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let mut version = [0u8; 1];
+        match reader.read_exact(&mut version) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(RcanError::EmptyToken)
+            }
+            Err(err) => return Err(RcanError::Io(err)),
+        }
+        if version[0] != VERSION {
+            return Err(RcanError::UnsupportedVersion(version[0]));
+        }
+
+        let mut reader = Tracked::new(reader);
+        let mut scratch = vec![0u8; SCRATCH_LEN];
+        // `Rcan`'s `Deserialize` verifies the signature over the rebuilt
+        // `DST`-prefixed preimage, as for `decode`.
+        match postcard::from_io::<Self, _>((&mut reader, &mut scratch)) {
+            Ok((rcan, _)) => Ok(rcan),
+            Err(err) => Err(match reader.error.take() {
+                Some(io) => RcanError::Io(io),
+                None => RcanError::Decode(err),
+            }),
+        }
+    }
+}
+
+/// Keeps the last error of the wrapped stream, which postcard replaces
+/// with its own.
+struct Tracked<T> {
+    inner: T,
+    error: Option<io::Error>,
+}
+
+impl<T> Tracked<T> {
+    fn new(inner: T) -> Self {
+        Self { inner, error: None }
+    }
+
+    fn track<V>(&mut self, result: io::Result<V>) -> io::Result<V> {
+        result.map_err(|err| {
+            let kind = err.kind();
+            self.error = Some(err);
+            kind.into()
+        })
+    }
+}
+
+impl<R: Read> Read for Tracked<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let result = self.inner.read(buf);
+        self.track(result)
+    }
+}
+
+impl<W: Write> Write for Tracked<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let result = self.inner.write(buf);
+        self.track(result)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        let result = self.inner.write_all(buf);
+        self.track(result)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.inner.flush();
+        self.track(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ed25519_dalek::SigningKey;
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::{test::Rpc, Expires};
+
+    #[test]
+    fn test_encode_to_decode_from() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .with_meta("ticket", "42")
+            .sign(Expires::At(1000));
+        let delegation = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+
+        // Same bytes as `encode`, and tokens read back one after another.
+        let mut stream = Vec::new();
+        root.encode_to(&mut stream)?;
+        assert_eq!(stream, root.encode());
+        delegation.encode_to(&mut stream)?;
+        let mut reader = stream.as_slice();
+        assert_eq!(Rcan::<Rpc>::decode_from(&mut reader)?, root);
+        assert_eq!(Rcan::<Rpc>::decode_from(&mut reader)?, delegation);
+        let err = Rcan::<Rpc>::decode_from(&mut reader).unwrap_err();
+        assert!(matches!(err, RcanError::EmptyToken), "{err}");
+
+        // The signature is checked.
+        let mut forged = root.encode();
+        let n = forged.len();
+        forged[n - 1] ^= 1;
+        assert!(Rcan::<Rpc>::decode_from(&mut forged.as_slice()).is_err());
+
+        let err = Rcan::<Rpc>::decode_from(&mut [VERSION + 1].as_slice()).unwrap_err();
+        assert!(matches!(err, RcanError::UnsupportedVersion(_)), "{err}");
+
+        // Failures of the stream are passed on.
+        let truncated = root.encode();
+        let mut reader = truncated[..10].chain(FailingReader);
+        let err = Rcan::<Rpc>::decode_from(&mut reader).unwrap_err();
+        assert!(
+            matches!(&err, RcanError::Io(io) if io.kind() == io::ErrorKind::ConnectionReset),
+            "{err}"
+        );
+        let err = root.encode_to(&mut [0u8; 10].as_mut_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+
+        Ok(())
+    }
+
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::ConnectionReset.into())
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! JSON encoding of [`Rcan`] tokens, for audit records and other JSON
+//! pipelines.
+//!
+//! The JSON form is an object with the payload fields and the signature.
+//! Keys and the signature are unpadded base64url strings, capabilities
+//! use their own serde implementation.
+//!
+//! The JSON encoding is *not* what is signed. The signature is always over
+//! the canonical postcard preimage, which [`Rcan::from_json`] rebuilds from
+//! the decoded fields before verifying. Re-serializing or hand-editing the
+//! JSON therefore can't produce a valid token for other fields, and
+//! signatures can't be checked by JSON tooling alone.
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{CapabilityOrigin, Expires, Meta, NotBefore, Payload, Rcan, RcanError, Result};
+
+/// `Caps` is `&[C]` when encoding and `Vec<C>` when decoding.
+#[derive(Serialize, Deserialize)]
+struct JsonRcan<Caps> {
+    issuer: Key,
+    audiences: Vec<Key>,
+    capability_origin: JsonOrigin,
+    capabilities: Caps,
+    valid_until: Expires,
+    valid_from: NotBefore,
+    issued_at: u64,
+    max_delegation_depth: Option<u8>,
+    meta: Meta,
+    audience_members: Vec<Key>,
+    delegation_audience_allowlist: Option<Vec<Key>>,
+    #[serde(with = "base64_signature")]
+    signature: Signature,
+}
+
+/// [`CapabilityOrigin`] with a base64 key.
+#[derive(Serialize, Deserialize)]
+enum JsonOrigin {
+    Issuer,
+    Delegation(Key),
+}
+
+/// A key as a base64 string.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct Key(#[serde(with = "base64_key")] VerifyingKey);
+
+/// Serde for keys as base64 strings.
+mod base64_key {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use ed25519_dalek::VerifyingKey;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        key: &VerifyingKey,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&URL_SAFE_NO_PAD.encode(key.as_bytes()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<VerifyingKey, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = URL_SAFE_NO_PAD.decode(encoded).map_err(D::Error::custom)?;
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| D::Error::custom("expected a 32 byte key"))?;
+        VerifyingKey::from_bytes(&bytes).map_err(D::Error::custom)
+    }
+}
+
+/// Serde for signatures as base64 strings.
+mod base64_signature {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use ed25519_dalek::Signature;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        signature: &Signature,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&URL_SAFE_NO_PAD.encode(signature.to_bytes()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Signature, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = URL_SAFE_NO_PAD.decode(encoded).map_err(D::Error::custom)?;
+        Signature::from_slice(&bytes).map_err(D::Error::custom)
+    }
+}
+
+impl<C> Rcan<C> {
+    /// Encodes the token as JSON.
+    ///
+    /// This is a transport encoding only, the signature stays over the
+    /// postcard preimage and is checked against it by [`Rcan::from_json`].
+    pub fn to_json(&self) -> String
+    where
+        C: Serialize,
+    {
+        let payload = &self.payload;
+        let json = JsonRcan {
+            issuer: Key(payload.issuer),
+            audiences: payload.audiences.iter().copied().map(Key).collect(),
+            capability_origin: match payload.capability_origin {
+                CapabilityOrigin::Issuer => JsonOrigin::Issuer,
+                CapabilityOrigin::Delegation(root) => JsonOrigin::Delegation(Key(root)),
+            },
+            capabilities: payload.capabilities.as_slice(),
+            valid_until: payload.valid_until.clone(),
+            valid_from: payload.valid_from.clone(),
+            issued_at: payload.issued_at,
+            max_delegation_depth: payload.max_delegation_depth,
+            meta: payload.meta.clone(),
+            audience_members: payload.audience_members.iter().copied().map(Key).collect(),
+            delegation_audience_allowlist: payload
+                .delegation_audience_allowlist
+                .as_ref()
+                .map(|keys| keys.iter().copied().map(Key).collect()),
+            signature: self.signature,
+        };
+        serde_json::to_string(&json).expect("capabilities serialize to JSON")
+    }
+
+    /// Decodes a token from JSON and verifies its signature over the
+    /// rebuilt postcard preimage.
+    pub fn from_json(json: &str) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let json: JsonRcan<Vec<C>> = serde_json::from_str(json)
+            .map_err(|err| RcanError::Malformed(format!("decoding JSON: {err}")))?;
+        if json.capabilities.is_empty() {
+            return Err(RcanError::Malformed("token without capabilities".into()));
+        }
+
+        let unwrap_keys = |keys: Vec<Key>| keys.into_iter().map(|Key(key)| key).collect();
+        let audiences: Vec<_> = unwrap_keys(json.audiences);
+        crate::check_audiences(&audiences).map_err(|err| RcanError::Malformed(err.into()))?;
+        let rcan = Rcan {
+            payload: Payload {
+                issuer: json.issuer.0,
+                audiences,
+                capability_origin: match json.capability_origin {
+                    JsonOrigin::Issuer => CapabilityOrigin::Issuer,
+                    JsonOrigin::Delegation(Key(root)) => CapabilityOrigin::Delegation(root),
+                },
+                capabilities: json.capabilities,
+                valid_until: json.valid_until,
+                valid_from: json.valid_from,
+                issued_at: json.issued_at,
+                max_delegation_depth: json.max_delegation_depth,
+                meta: json.meta,
+                audience_members: unwrap_keys(json.audience_members),
+                delegation_audience_allowlist: json.delegation_audience_allowlist.map(unwrap_keys),
+            },
+            signature: json.signature,
+        };
+        rcan.verify_signature()?;
+        Ok(rcan)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use ed25519_dalek::SigningKey;
+    use testresult::TestResult;
+
+    use crate::{test::Rpc, Expires, Rcan};
+
+    #[test]
+    fn test_json_roundtrip() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .with_meta("ticket", "42")
+            .sign(Expires::At(1000));
+        let delegation = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+
+        for rcan in [root, delegation] {
+            let json = rcan.to_json();
+            let value: serde_json::Value = serde_json::from_str(&json)?;
+            assert!(value["issuer"].is_string());
+            assert!(value["signature"].is_string());
+            assert_eq!(Rcan::<Rpc>::from_json(&json)?, rcan);
+        }
+
+        // The signature covers the fields, not the JSON text.
+        let rcan =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read).sign(Expires::Never);
+        let tampered = rcan.to_json().replace("\"Read\"", "\"All\"");
+        assert!(Rcan::<Rpc>::from_json(&tampered).is_err());
+
+        Ok(())
+    }
+}
@@ -1,59 +1,223 @@
-use std::ops::Add;
-
-// TODO: better error management
-use anyhow::{bail, ensure, Context, Result};
-use ed25519_dalek::{
-    ed25519::signature::Signer, Signature, SigningKey, VerifyingKey, SIGNATURE_LENGTH,
-};
-use n0_future::time::{Duration, SystemTime};
+//! Really simple user Controlled Authorization Networks.
+//!
+//! The types needed to mint and check tokens are re-exported, so
+//! depending on `rcan` alone is enough:
+//!
+//! ```
+//! use rcan::{caps::EpochCapability, Authorizer, Expires, Rcan, Result, SigningKey};
+//!
+//! fn main() -> Result<()> {
+//!     let service = SigningKey::from_bytes(&[0u8; 32]);
+//!     let alice = SigningKey::from_bytes(&[1u8; 32]);
+//!     let rcan = Rcan::issuing_builder(&service, alice.verifying_key(), EpochCapability(1))
+//!         .sign(Expires::valid_for_secs(60));
+//!     let rcan = Rcan::decode(&rcan.encode())?;
+//!
+//!     let proof = Authorizer::new(service.verifying_key()).check_epoch_invocation_from(
+//!         alice.verifying_key(),
+//!         1,
+//!         &[&rcan],
+//!     )?;
+//!     assert!(proof.expires().is_valid_at(rcan::SystemTime::now()));
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Without the default `std` feature the crate is `no_std`, and only needs
+//! `alloc`. Tokens still encode, decode and verify, but there is no system
+//! clock: check invocations with [`Authorizer::check_invocation_from_at`]
+//! and a [`Position`] from a trusted clock, in unix seconds.
+//!
+//! Issuers sign with ed25519-dalek's [`SigningKey`], which is zeroized on
+//! drop, as is the expanded key held by a [`BatchIssuer`]. Keep long-lived
+//! keys in those types rather than as raw bytes. With the `zeroize`
+//! feature the buffers signed on the way are zeroized after use as well.
+//! That is all the feature covers for issuers: builders only borrow the
+//! issuer, and apart from a [`BatchIssuer`]'s expansion the crate never
+//! copies its key, so wiping the key is up to the type that holds it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{collections::BTreeMap, format, string::String, sync::Arc, vec, vec::Vec};
+
+pub use core::time::Duration;
+use ed25519_dalek::{ed25519::signature::Signer, SIGNATURE_LENGTH};
+pub use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+#[cfg(feature = "std")]
+pub use n0_future::time::SystemTime;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-pub const VERSION: u8 = 1;
+mod batch;
+pub mod caps;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod chain;
+#[cfg(feature = "compact")]
+mod compact;
+mod dynamic;
+mod error;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "base64")]
+mod pem;
+#[cfg(feature = "async")]
+mod renewal;
+mod scheme;
+mod store;
+#[cfg(feature = "base64")]
+mod string;
+#[cfg(feature = "zeroize")]
+mod zeroizing;
+
+pub use batch::BatchIssuer;
+pub use chain::{ProofChain, RcanChain};
+pub use dynamic::DynCapability;
+pub use error::{BoxError, KeyBytes, RcanError};
+#[cfg(feature = "async")]
+pub use renewal::RenewalManager;
+pub use scheme::{Ed25519, SignatureScheme};
+pub use store::RcanStore;
+
+#[doc(hidden)]
+pub mod testing;
+
+/// Result type of this crate.
+pub type Result<T, E = RcanError> = core::result::Result<T, E>;
+
+/// Wire format version, prefixed to every encoded token.
+///
+/// Version 2 added `valid_from`, `issued_at`, `max_delegation_depth`, `meta`,
+/// `audience_members` and `delegation_audience_allowlist` to the payload,
+/// after `valid_until`, and replaced the single `audience` and
+/// `capability` with lists of `audiences` and `capabilities`. Tokens of
+/// earlier versions are rejected by [`Rcan::decode`].
+pub const VERSION: u8 = 2;
 
 /// Domain separation tag
 pub const DST: &[u8] = b"rcan-1-delegation";
 
-/// Stable serde for [`VerifyingKey`]: length-prefixed bytes in binary
-/// formats, lowercase hex in human-readable ones. Goes through
-/// [`serdect`] for its constant-time hex codec, and pins the wire
-/// format independent of [`ed25519_dalek`]'s own serde impl.
+/// Stable serde for a scheme's verifying keys, [`VerifyingKey`] by
+/// default: length-prefixed bytes in binary formats, lowercase hex in
+/// human-readable ones. Goes through [`serdect`] for its constant-time hex
+/// codec, and pins the wire format independent of [`ed25519_dalek`]'s own
+/// serde impl.
 mod verifying_key_serde {
-    use ed25519_dalek::VerifyingKey;
     use serde::{de::Error, Deserializer, Serializer};
 
-    pub fn serialize<S: Serializer>(
-        key: &VerifyingKey,
-        serializer: S,
-    ) -> std::result::Result<S::Ok, S::Error> {
-        serdect::array::serialize_hex_lower_or_bin(key.as_bytes(), serializer)
+    use crate::SignatureScheme;
+
+    pub fn serialize<S: SignatureScheme, Ser: Serializer>(
+        key: &S::VerifyingKey,
+        serializer: Ser,
+    ) -> core::result::Result<Ser::Ok, Ser::Error> {
+        serdect::slice::serialize_hex_lower_or_bin(&S::key_to_bytes(key), serializer)
+    }
+
+    pub fn deserialize<'de, S: SignatureScheme, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<S::VerifyingKey, D::Error> {
+        let bytes = serdect::slice::deserialize_hex_or_bin_vec(deserializer)?;
+        S::key_from_bytes(&bytes).map_err(D::Error::custom)
+    }
+}
+
+/// [`verifying_key_serde`] for a list of keys.
+mod verifying_keys_serde {
+    use alloc::vec::Vec;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::SignatureScheme;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(transparent, bound = "")]
+    struct Key<S: SignatureScheme>(
+        #[serde(
+            serialize_with = "super::verifying_key_serde::serialize::<S, _>",
+            deserialize_with = "super::verifying_key_serde::deserialize::<S, _>"
+        )]
+        S::VerifyingKey,
+    );
+
+    pub fn serialize<S: SignatureScheme, Ser: Serializer>(
+        keys: &[S::VerifyingKey],
+        serializer: Ser,
+    ) -> core::result::Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(keys.iter().map(|key| Key::<S>(*key)))
+    }
+
+    pub fn deserialize<'de, S: SignatureScheme, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Vec<S::VerifyingKey>, D::Error> {
+        let keys = Vec::<Key<S>>::deserialize(deserializer)?;
+        Ok(keys.into_iter().map(|Key(key)| key).collect())
+    }
+}
+
+/// [`verifying_keys_serde`] for an optional list of keys.
+mod optional_verifying_keys_serde {
+    use alloc::vec::Vec;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::SignatureScheme;
+
+    struct KeysRef<'a, S: SignatureScheme>(&'a [S::VerifyingKey]);
+
+    impl<S: SignatureScheme> Serialize for KeysRef<'_, S> {
+        fn serialize<Ser: Serializer>(
+            &self,
+            serializer: Ser,
+        ) -> core::result::Result<Ser::Ok, Ser::Error> {
+            super::verifying_keys_serde::serialize::<S, _>(self.0, serializer)
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(transparent, bound = "")]
+    struct Keys<S: SignatureScheme>(
+        #[serde(deserialize_with = "super::verifying_keys_serde::deserialize::<S, _>")]
+        Vec<S::VerifyingKey>,
+    );
+
+    pub fn serialize<S: SignatureScheme, Ser: Serializer>(
+        keys: &Option<Vec<S::VerifyingKey>>,
+        serializer: Ser,
+    ) -> core::result::Result<Ser::Ok, Ser::Error> {
+        keys.as_deref().map(KeysRef::<S>).serialize(serializer)
     }
 
-    pub fn deserialize<'de, D: Deserializer<'de>>(
+    pub fn deserialize<'de, S: SignatureScheme, D: Deserializer<'de>>(
         deserializer: D,
-    ) -> std::result::Result<VerifyingKey, D::Error> {
-        let mut buf = [0u8; 32];
-        serdect::array::deserialize_hex_or_bin(&mut buf, deserializer)?;
-        VerifyingKey::from_bytes(&buf).map_err(D::Error::custom)
+    ) -> core::result::Result<Option<Vec<S::VerifyingKey>>, D::Error> {
+        let keys = Option::<Keys<S>>::deserialize(deserializer)?;
+        Ok(keys.map(|Keys(keys)| keys))
     }
 }
 
-/// Wire-format wrapper around an ed25519 [`Signature`] that serializes as
-/// a fixed-length tuple of `SIGNATURE_LENGTH` bytes (no length prefix in
-/// binary formats like postcard), and as a lowercase hex string in
-/// human-readable formats.
-struct SignatureWire([u8; SIGNATURE_LENGTH]);
+/// Wire-format wrapper around a scheme's signature, an ed25519
+/// [`Signature`] by default, that serializes as a fixed-length tuple of
+/// [`SignatureScheme::SIGNATURE_LENGTH`] bytes (no length prefix in binary
+/// formats like postcard), and as a lowercase hex string in human-readable
+/// formats.
+struct SignatureWire<S: SignatureScheme = Ed25519>(S::Signature);
 
-impl Serialize for SignatureWire {
-    fn serialize<S: serde::Serializer>(
+impl<S: SignatureScheme> Serialize for SignatureWire<S> {
+    fn serialize<Ser: serde::Serializer>(
         &self,
-        serializer: S,
-    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer: Ser,
+    ) -> core::result::Result<Ser::Ok, Ser::Error> {
+        let bytes = S::signature_to_bytes(&self.0);
         if serializer.is_human_readable() {
-            serializer.collect_str(&format_args!("{}", hex::encode(self.0)))
+            serializer.collect_str(&format_args!("{}", hex::encode(bytes)))
         } else {
             use serde::ser::SerializeTuple;
-            let mut tup = serializer.serialize_tuple(SIGNATURE_LENGTH)?;
-            for b in &self.0 {
+            let mut tup = serializer.serialize_tuple(S::SIGNATURE_LENGTH)?;
+            for b in &bytes {
                 tup.serialize_element(b)?;
             }
             tup.end()
@@ -61,57 +225,57 @@ impl Serialize for SignatureWire {
     }
 }
 
-impl<'de> Deserialize<'de> for SignatureWire {
+impl<'de, S: SignatureScheme> Deserialize<'de> for SignatureWire<S> {
     fn deserialize<D: serde::Deserializer<'de>>(
         deserializer: D,
-    ) -> std::result::Result<Self, D::Error> {
-        struct V;
-        impl<'de> serde::de::Visitor<'de> for V {
-            type Value = SignatureWire;
+    ) -> core::result::Result<Self, D::Error> {
+        struct V<S>(core::marker::PhantomData<S>);
+        impl<'de, S: SignatureScheme> serde::de::Visitor<'de> for V<S> {
+            type Value = SignatureWire<S>;
 
-            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "an ed25519 signature ({} bytes)", SIGNATURE_LENGTH)
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "a signature ({} bytes)", S::SIGNATURE_LENGTH)
             }
 
             fn visit_str<E: serde::de::Error>(
                 self,
                 v: &str,
-            ) -> std::result::Result<Self::Value, E> {
-                let mut bytes = [0u8; SIGNATURE_LENGTH];
-                hex::decode_to_slice(v, &mut bytes).map_err(E::custom)?;
-                Ok(SignatureWire(bytes))
+            ) -> core::result::Result<Self::Value, E> {
+                let bytes = hex::decode(v).map_err(E::custom)?;
+                self.visit_bytes(&bytes)
             }
 
             fn visit_bytes<E: serde::de::Error>(
                 self,
                 v: &[u8],
-            ) -> std::result::Result<Self::Value, E> {
-                if v.len() != SIGNATURE_LENGTH {
+            ) -> core::result::Result<Self::Value, E> {
+                if v.len() != S::SIGNATURE_LENGTH {
                     return Err(E::invalid_length(v.len(), &self));
                 }
-                let mut bytes = [0u8; SIGNATURE_LENGTH];
-                bytes.copy_from_slice(v);
-                Ok(SignatureWire(bytes))
+                S::signature_from_bytes(v)
+                    .map(SignatureWire)
+                    .map_err(E::custom)
             }
 
-            fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+            fn visit_seq<A>(self, mut seq: A) -> core::result::Result<Self::Value, A::Error>
             where
                 A: serde::de::SeqAccess<'de>,
             {
-                let mut bytes = [0u8; SIGNATURE_LENGTH];
+                let mut bytes = vec![0u8; S::SIGNATURE_LENGTH];
                 for (i, slot) in bytes.iter_mut().enumerate() {
                     *slot = seq
                         .next_element()?
                         .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
                 }
-                Ok(SignatureWire(bytes))
+                self.visit_bytes(&bytes)
             }
         }
 
+        let visitor = V(core::marker::PhantomData);
         if deserializer.is_human_readable() {
-            deserializer.deserialize_str(V)
+            deserializer.deserialize_str(visitor)
         } else {
-            deserializer.deserialize_tuple(SIGNATURE_LENGTH, V)
+            deserializer.deserialize_tuple(S::SIGNATURE_LENGTH, visitor)
         }
     }
 }
@@ -132,472 +296,4903 @@ pub trait Capability: Serialize {
     /// Returns `true` if `self` grants permission to perform the `other` capability,
     /// otherwise returns `false`.
     fn permits(&self, other: &Self) -> bool;
-}
-
-/// An authorizer for invocations.
-///
-/// This represents an identity in the form of a public key.
-/// This public key will always be the same as the original issuer of
-/// the capabilities that are invoked against the authorizer.
-#[derive(Clone, Copy, PartialEq, Eq)]
-pub struct Authorizer {
-    // Might even make that `SigningKey` and allow it to `sign` rcans?
-    identity: VerifyingKey,
-}
 
-impl Authorizer {
-    /// Constructs a new authorizer for given identity.
-    pub fn new(identity: VerifyingKey) -> Self {
-        Self { identity }
+    /// Fallible variant of [`Capability::permits`], for capabilities whose
+    /// evaluation depends on something that can fail, like an external
+    /// policy source.
+    ///
+    /// Used by [`Authorizer::check_invocation_from_verbose`] to tell denials
+    /// apart from evaluation errors. Implementations overriding this should
+    /// keep `permits` returning `false` wherever this returns an error.
+    fn try_permits(&self, other: &Self) -> Result<bool, BoxError> {
+        Ok(self.permits(other))
     }
 
-    /// Verifies an invocation of a capability owned by this authorizer,
-    /// that may have been passed through delegations in a proof chain
-    /// and was finally signed back to us from given `invoker`.
+    /// Explains why `self` permits `other`, for audit trails.
     ///
-    /// Make sure to verify that the `invoker` signed and authenticated the
-    /// message containing the `capability`.
-    pub fn check_invocation_from<C: Capability>(
-        &self,
-        invoker: VerifyingKey,
-        capability: C,
-        proof_chain: &[&Rcan<C>],
-    ) -> Result<()> {
-        let now = SystemTime::now();
-        // We require that proof chains are provided "back-to-front".
-        // So they start with the owner of the capability, then
-        // proceed with the next item in the chain.
-        let mut current_issuer_target = &self.identity;
-        for proof in proof_chain {
-            // Verify proof chain issuer/audience integrity:
-            let issuer = &proof.payload.issuer;
-            let audience = &proof.payload.audience;
-            ensure!(
-                issuer == current_issuer_target,
-                "invocation failed: expected proof to be issued by {}, but was issued by {}",
-                hex::encode(current_issuer_target),
-                hex::encode(issuer),
-            );
-
-            // Verify each proof's time validity:
-            let expiry = &proof.payload.valid_until;
-            ensure!(
-                expiry.is_valid_at(now),
-                "invocation failed: proof expired at {expiry}"
-            );
-
-            // Verify that the capability is actually reached through:
-            ensure!(
-                proof.capability_issuer() == &self.identity,
-                "invocation failed: proof is missing delegation for capability of {}",
-                hex::encode(self.identity)
-            );
+    /// Richer capability types can override this to say which rule or
+    /// bound was satisfied. Only meaningful where [`Capability::permits`]
+    /// returns `true`. The default has no explanation and returns `None`.
+    /// Surfaced through [`Authorizer::explain_invocation`].
+    fn permits_with_witness(&self, other: &Self) -> Option<Witness> {
+        let _ = other;
+        None
+    }
 
-            // Verify that the capability doesn't break out of capabilitys:
-            ensure!(
-                proof.payload.capability().permits(&capability),
-                "invocation failed"
-            );
+    /// Whether a token granting this capability may be re-delegated.
+    ///
+    /// A proof may only be followed by another one in a chain if all of
+    /// its capabilities allow it, see [`RcanError::NotDelegatable`]. The
+    /// default always allows it, override it for capabilities like
+    /// [`AccessCapability`](caps::AccessCapability) where only some levels
+    /// may pass access on.
+    fn may_delegate(&self) -> bool {
+        true
+    }
 
-            // Continue checking the proof chain's integrity with this
-            // delegation's audience as the next issuer target:
-            current_issuer_target = audience;
+    /// The capability permitting what both `self` and `other` permit, their
+    /// greatest lower bound, or `None` if they have nothing in common.
+    ///
+    /// The default handles capabilities where one permits the other and
+    /// returns the narrower of the two. For any other pair it returns
+    /// `None`, so capability types with partial overlaps should override
+    /// it. Used to attenuate capabilities along a chain, see
+    /// [`Authorizer::effective_capability`].
+    fn meet(&self, other: &Self) -> Option<Self>
+    where
+        Self: Sized + Clone,
+    {
+        if self.permits(other) {
+            Some(other.clone())
+        } else if other.permits(self) {
+            Some(self.clone())
+        } else {
+            None
         }
+    }
 
-        ensure!(
-            &invoker == current_issuer_target,
-            "invocation failed: expected delegation chain to end in the connection's owner {}, but the connection is authenticated by {} instead",
-            hex::encode(invoker),
-            hex::encode(current_issuer_target),
-        );
-
-        Ok(())
+    /// The narrowest capability permitting everything `self` and `other`
+    /// permit, their least upper bound, or `None` if there is none.
+    ///
+    /// Like [`Capability::meet`], the default returns the wider of the two
+    /// where one permits the other and `None` otherwise. Used to merge the
+    /// capabilities of a token, see [`Authorizer::effective_capability`].
+    fn join(&self, other: &Self) -> Option<Self>
+    where
+        Self: Sized + Clone,
+    {
+        if self.permits(other) {
+            Some(self.clone())
+        } else if other.permits(self) {
+            Some(other.clone())
+        } else {
+            None
+        }
     }
 }
 
-/// A token for attenuated capability delegations
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Rcan<C> {
-    /// The actual content.
-    pub payload: Payload<C>,
-    /// Signature over the serialized payload.
-    pub signature: Signature,
+/// `capability` attenuated by every proof in `proofs`, with
+/// [`Capability::meet`], or `None` if some proof grants nothing of it.
+///
+/// A proof with several capabilities attenuates it to the
+/// [join](Capability::join) of their meets with it, and to `None` if
+/// those have no join.
+fn attenuate<C: Capability + Clone>(capability: &C, proofs: &[&Rcan<C>]) -> Option<C> {
+    proofs
+        .iter()
+        .try_fold(capability.clone(), |capability, proof| {
+            join_all(
+                proof
+                    .capabilities()
+                    .iter()
+                    .filter_map(|granted| granted.meet(&capability)),
+            )
+        })
 }
 
-impl<C: Serialize> Serialize for Rcan<C> {
-    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        use serde::ser::SerializeTuple;
-        let mut tup = serializer.serialize_tuple(2)?;
-        tup.serialize_element(&self.payload)?;
-        tup.serialize_element(&SignatureWire(self.signature.to_bytes()))?;
-        tup.end()
+/// The [join](Capability::join) of all `capabilities`, `None` if there are
+/// none or they have no join.
+fn join_all<C: Capability + Clone>(mut capabilities: impl Iterator<Item = C>) -> Option<C> {
+    let first = capabilities.next()?;
+    capabilities.try_fold(first, |acc, capability| acc.join(&capability))
+}
+
+/// Evidence for why a capability permitted another, see
+/// [`Capability::permits_with_witness`].
+#[derive(Clone, Debug, PartialEq, Eq, derive_more::Display)]
+#[display("{_0}")]
+pub struct Witness(String);
+
+impl Witness {
+    /// Creates a witness from a human-readable explanation.
+    pub fn new(explanation: impl Into<String>) -> Self {
+        Self(explanation.into())
+    }
+
+    /// The explanation.
+    pub fn as_str(&self) -> &str {
+        &self.0
     }
 }
 
-impl<'de, C: Deserialize<'de> + Serialize> Deserialize<'de> for Rcan<C> {
-    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct RcanVisitor<C>(std::marker::PhantomData<C>);
+/// A capability with a placeholder, for minting many similar capabilities
+/// that differ only in a parameter such as a resource id.
+///
+/// Used with [`RcanBuilder::issue_from_template`].
+pub trait CapabilityTemplate<C> {
+    /// What is substituted for the placeholder.
+    type Param: ?Sized;
 
-        impl<'de, C: Deserialize<'de> + Serialize> serde::de::Visitor<'de> for RcanVisitor<C> {
-            type Value = Rcan<C>;
+    /// The capability with `param` substituted for the placeholder.
+    fn instantiate(&self, param: &Self::Param) -> C;
+}
 
-            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                f.write_str("an rcan token (payload, signature)")
-            }
+/// An asynchronous source of revocations, such as a remote revocation
+/// service, consulted by [`Authorizer::check_invocation_from_async`].
+#[cfg(feature = "async")]
+pub trait AsyncRevocationSet<C> {
+    /// Resolves to `true` if `proof` has been revoked.
+    ///
+    /// Errors, e.g. when the service is unreachable, fail the invocation.
+    fn is_revoked(
+        &self,
+        proof: &Rcan<C>,
+    ) -> impl core::future::Future<Output = Result<bool, BoxError>>;
+}
 
-            fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'de>,
-            {
-                let payload: Payload<C> = seq
-                    .next_element()?
-                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
-                let SignatureWire(sig_bytes) = seq
-                    .next_element()?
-                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
-                let rcan = Rcan {
-                    payload,
-                    signature: Signature::from_bytes(&sig_bytes),
-                };
+/// A key that signs tokens, possibly asynchronously, e.g. one held in an
+/// HSM or a remote key management service.
+///
+/// Builders created with a signer are signed with
+/// [`RcanBuilder::sign_with`]. The signer gets the same preimage
+/// [`RcanBuilder::sign`] signs, so the tokens are the same either way.
+pub trait AsyncSigner {
+    /// Resolves to the signature of `msg` by [`AsyncSigner::verifying_key`].
+    ///
+    /// Errors, e.g. when the key service is unreachable, fail the signing.
+    fn sign(&self, msg: &[u8]) -> impl core::future::Future<Output = Result<Signature, BoxError>>;
 
-                // Verify before yielding, so a deserialized `Rcan` is
-                // always signature checked. Without this, serde wire
-                // formats hand back an unverified token while only
-                // `decode` checks the signature.
-                rcan.verify_signature().map_err(serde::de::Error::custom)?;
+    /// The public key of the signer, which becomes the token's issuer.
+    fn verifying_key(&self) -> VerifyingKey;
+}
 
-                Ok(rcan)
-            }
-        }
+impl AsyncSigner for SigningKey {
+    async fn sign(&self, msg: &[u8]) -> Result<Signature, BoxError> {
+        Ok(Signer::sign(self, msg))
+    }
 
-        deserializer.deserialize_tuple(2, RcanVisitor::<C>(std::marker::PhantomData))
+    fn verifying_key(&self) -> VerifyingKey {
+        SigningKey::verifying_key(self)
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, derive_more::Debug, PartialEq, Eq)]
-pub struct Payload<C> {
-    /// The issuer
-    #[debug("{}", hex::encode(issuer))]
-    #[serde(with = "verifying_key_serde")]
-    issuer: VerifyingKey,
-    /// The intended audience
-    #[debug("{}", hex::encode(audience))]
-    #[serde(with = "verifying_key_serde")]
-    audience: VerifyingKey,
-    /// The origin of the capability
-    capability_origin: CapabilityOrigin,
-    /// The capability
-    capability: C,
-    /// Valid until unix timestamp in seconds.
-    valid_until: Expires,
+/// The issuer a [`RcanBuilder`] is created with: an [`AsyncSigner`] or
+/// [`SigningKey`] to sign the token with, or just a [`VerifyingKey`] to
+/// sign the token elsewhere, see [`RcanBuilder::to_signing_payload`].
+///
+/// Issuers of tokens of another [`SignatureScheme`] implement
+/// `Issuer<S>` for that scheme, which picks the scheme of the builder.
+pub trait Issuer<S: SignatureScheme = Ed25519> {
+    /// The public key of the issuer.
+    fn verifying_key(&self) -> S::VerifyingKey;
 }
 
-impl<C> Payload<C> {
-    pub fn capability(&self) -> &C {
-        &self.capability
+impl<A: AsyncSigner + ?Sized> Issuer for A {
+    fn verifying_key(&self) -> VerifyingKey {
+        AsyncSigner::verifying_key(self)
     }
+}
 
-    pub fn capability_origin(&self) -> &CapabilityOrigin {
-        &self.capability_origin
+impl Issuer for VerifyingKey {
+    fn verifying_key(&self) -> VerifyingKey {
+        *self
     }
 }
 
-/// The potential origins of a capability.
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
-pub enum CapabilityOrigin {
-    /// The origin is the issuer itself
-    Issuer,
-    /// This is a delegation, with this key being the root of the delegation chain.
-    Delegation(#[serde(with = "verifying_key_serde")] VerifyingKey),
+/// An authorizer for invocations.
+///
+/// This represents an identity in the form of a public key.
+/// This public key will always be the same as the original issuer of
+/// the capabilities that are invoked against the authorizer.
+///
+/// Authorizers of tokens of another [`SignatureScheme`] than [`Ed25519`]
+/// check invocations with [`Authorizer::check_invocation_from_at`] and
+/// [`Authorizer::check_invocation_from`], the other checks are ed25519
+/// only.
+#[derive(Clone)]
+pub struct Authorizer<S: SignatureScheme = Ed25519> {
+    // Might even make that `SigningKey` and allow it to `sign` rcans?
+    identity: S::VerifyingKey,
+    /// In unix seconds.
+    issuance_floor: Option<u64>,
+    max_depth: usize,
+    max_window: Option<Duration>,
+    monotonic_expiry: bool,
+    revocations: Option<Arc<IsRevoked>>,
 }
 
-/// When an rcan expires
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, derive_more::Display)]
-pub enum Expires {
-    /// Never expires
-    #[display("never")]
-    Never,
-    /// Valid until given unix timestamp in seconds
-    #[display("{_0}")]
-    At(u64),
-}
+/// The predicate of [`Authorizer::with_revocations`].
+type IsRevoked = dyn Fn(&RcanId) -> bool + Send + Sync;
 
-pub struct RcanBuilder<'s, C> {
-    issuer: &'s SigningKey,
-    audience: VerifyingKey,
-    capability_origin: CapabilityOrigin,
-    capability: C,
+/// Authorizers are equal if they are configured the same, with the same
+/// revocation predicate instance, if any.
+impl<S: SignatureScheme> PartialEq for Authorizer<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity == other.identity
+            && self.issuance_floor == other.issuance_floor
+            && self.max_depth == other.max_depth
+            && self.max_window == other.max_window
+            && self.monotonic_expiry == other.monotonic_expiry
+            && match (&self.revocations, &other.revocations) {
+                (None, None) => true,
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                _ => false,
+            }
+    }
 }
 
-impl<C> Rcan<C> {
-    pub fn issuing_builder(
-        issuer: &SigningKey,
-        audience: VerifyingKey,
-        capability: C,
-    ) -> RcanBuilder<'_, C> {
-        RcanBuilder {
-            issuer,
-            audience,
-            capability_origin: CapabilityOrigin::Issuer,
-            capability,
+impl<S: SignatureScheme> Eq for Authorizer<S> {}
+
+impl<S: SignatureScheme> Authorizer<S> {
+    /// Constructs a new authorizer for given identity.
+    pub fn new(identity: S::VerifyingKey) -> Self {
+        Self {
+            identity,
+            issuance_floor: None,
+            max_depth: Authorizer::DEFAULT_MAX_DEPTH,
+            max_window: None,
+            monotonic_expiry: false,
+            revocations: None,
         }
     }
 
-    pub fn delegating_builder(
-        issuer: &SigningKey,
-        audience: VerifyingKey,
-        owner: VerifyingKey,
-        capability: C,
-    ) -> RcanBuilder<'_, C> {
-        RcanBuilder {
-            issuer,
-            audience,
-            capability_origin: CapabilityOrigin::Delegation(owner),
-            capability,
-        }
+    /// Rejects proof chains with more than `max_depth` proofs, instead of
+    /// [`Authorizer::DEFAULT_MAX_DEPTH`].
+    ///
+    /// This bounds the work an invoker can cause with a long chain. Checks
+    /// fail as soon as they reach the first proof past the limit.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
     }
 
-    pub fn encode(&self) -> Vec<u8>
-    where
-        C: Serialize,
-    {
-        postcard::to_extend(self, vec![VERSION]).expect("vec")
+    /// Rejects chains whose effective validity window is longer than
+    /// `max_window`, with an [`RcanError::ValidityWindowExceeded`] error.
+    ///
+    /// The window runs from the latest start of any proof, its
+    /// [`Rcan::valid_from`] or else its [`Rcan::issued_at`], to the earliest
+    /// [`Rcan::expires`]. Chains that never expire on the wall clock always
+    /// exceed it. Catches over-long delegations.
+    pub fn with_max_window(mut self, max_window: Duration) -> Self {
+        self.max_window = Some(max_window);
+        self
     }
 
-    pub fn decode(bytes: &[u8]) -> Result<Self>
-    where
-        C: DeserializeOwned + Serialize,
-    {
-        let Some(version) = bytes.first() else {
-            bail!("cannot decode, token is empty");
-        };
-        ensure!(*version == VERSION, "invalid version: {}", version);
-        // `Rcan`'s `Deserialize` verifies the signature, so a successful
-        // decode is already signature-checked.
-        let rcan: Self = postcard::from_bytes(&bytes[1..]).context("decoding")?;
-        Ok(rcan)
+    /// Rejects chains where a delegation expires after the proof before
+    /// it, with an [`RcanError::OutlivesParent`] error.
+    ///
+    /// Every proof is checked against its own expiry either way, so this
+    /// doesn't extend what a chain grants. It catches delegations that
+    /// claim to outlive their parent. A proof that never expires only
+    /// follows one that doesn't either, and expiries on different clocks
+    /// never follow each other.
+    pub fn with_monotonic_expiry(mut self) -> Self {
+        self.monotonic_expiry = true;
+        self
     }
 
-    /// Verify the signature over the payload. The signed bytes are
-    /// `DST ++ postcard(payload)`, matching [`RcanBuilder::sign`].
-    fn verify_signature(&self) -> Result<()>
-    where
-        C: Serialize,
-    {
-        let signed = postcard::to_extend(&self.payload, DST.to_vec())?;
-        self.payload
-            .issuer
-            .verify_strict(&signed, &self.signature)?;
-        Ok(())
+    /// Rejects every proof issued before `floor`, regardless of its expiry,
+    /// with an [`RcanError::IssuedBeforeFloor`] error.
+    ///
+    /// A blanket revocation of everything minted before, e.g., a security
+    /// incident. Compared in whole seconds, like [`Rcan::issued_at`].
+    #[cfg(feature = "std")]
+    pub fn with_issuance_floor(mut self, floor: SystemTime) -> Self {
+        self.issuance_floor = Some(unix_secs(floor));
+        self
     }
 
-    pub fn audience(&self) -> &VerifyingKey {
-        &self.payload.audience
+    /// Rejects every proof whose [`Rcan::id`] `is_revoked`, with an
+    /// [`RcanError::Revoked`] error naming the proof.
+    ///
+    /// For killing tokens right away, e.g. after a key compromise, without
+    /// waiting for them to expire.
+    pub fn with_revocations(
+        mut self,
+        is_revoked: impl Fn(&RcanId) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.revocations = Some(Arc::new(is_revoked));
+        self
     }
 
-    pub fn issuer(&self) -> &VerifyingKey {
-        &self.payload.issuer
+    /// Verifies an invocation of a capability owned by this authorizer,
+    /// that may have been passed through delegations in a proof chain
+    /// and was finally signed back to us from given `invoker`.
+    ///
+    /// Make sure to verify that the `invoker` signed and authenticated the
+    /// message containing the `capability`.
+    #[cfg(feature = "std")]
+    pub fn check_invocation_from<C: Capability>(
+        &self,
+        invoker: S::VerifyingKey,
+        capability: C,
+        proof_chain: &[&Rcan<C, S>],
+    ) -> Result<InvocationProof> {
+        self.check_invocation_from_at(SystemTime::now(), invoker, capability, proof_chain)
     }
 
-    pub fn capability(&self) -> &C {
-        self.payload.capability()
+    /// Verifies an invocation like [`Authorizer::check_invocation_from`], but
+    /// checks the proofs' time validity at `at` instead of now.
+    ///
+    /// Use this for deterministic tests, with a trusted clock other than
+    /// the system's, or to pre-authorize work scheduled for a future
+    /// instant. Pass a [`Position::Block`] to check tokens that expire at a
+    /// block height.
+    pub fn check_invocation_from_at<C: Capability>(
+        &self,
+        at: impl Into<Position>,
+        invoker: S::VerifyingKey,
+        capability: C,
+        proof_chain: &[&Rcan<C, S>],
+    ) -> Result<InvocationProof> {
+        self.verify_invocation_at(at.into(), invoker, &capability, proof_chain)
     }
 
-    pub fn capability_origin(&self) -> &CapabilityOrigin {
-        self.payload.capability_origin()
+    /// See [`Authorizer::check_invocation_from_at`].
+    fn verify_invocation_at<C: Capability>(
+        &self,
+        at: Position,
+        invoker: S::VerifyingKey,
+        capability: &C,
+        proof_chain: &[&Rcan<C, S>],
+    ) -> Result<InvocationProof> {
+        self.check_chain_integrity(at, invoker, proof_chain)?;
+
+        // Verify that the capability doesn't break out of capabilitys:
+        check_permitted(proof_chain, capability)?;
+
+        Ok(InvocationProof::for_chain(proof_chain))
     }
 
-    pub fn capability_issuer(&self) -> &VerifyingKey {
-        match self.payload.capability_origin() {
-            CapabilityOrigin::Issuer => &self.payload.issuer,
-            CapabilityOrigin::Delegation(ref root) => root,
+    /// Verifies everything about a proof chain except the capabilities
+    /// it grants: issuer/audience linkage, expiry, the capability origin
+    /// and that the chain ends in `invoker`.
+    ///
+    /// Expiry is checked against `now`.
+    fn check_chain_integrity<C: Capability>(
+        &self,
+        now: Position,
+        invoker: S::VerifyingKey,
+        proof_chain: &[&Rcan<C, S>],
+    ) -> Result<()> {
+        // We require that proof chains are provided "back-to-front".
+        // So they start with the owner of the capability, then
+        // proceed with the next item in the chain.
+        let mut cursor = ChainCursor::new(self);
+        let mut parent = None;
+        for proof in proof_chain {
+            self.check_next_proof(now, &mut cursor, parent, proof)?;
+            parent = Some(*proof);
         }
+        cursor.check_end(invoker, parent)
     }
 
-    pub fn expires(&self) -> &Expires {
-        &self.payload.valid_until
-    }
-}
+    /// Verifies the proof at `cursor` in a chain, given its `parent`, and
+    /// advances the cursor. See [`Authorizer::check_chain_integrity`].
+    fn check_next_proof<C: Capability>(
+        &self,
+        now: Position,
+        cursor: &mut ChainCursor<S>,
+        parent: Option<&Rcan<C, S>>,
+        proof: &Rcan<C, S>,
+    ) -> Result<()> {
+        let i = cursor.index;
 
-impl<C> RcanBuilder<'_, C> {
-    pub fn sign(self, valid_until: Expires) -> Rcan<C>
-    where
-        C: Serialize,
-    {
-        let payload = Payload {
-            issuer: self.issuer.verifying_key(),
-            audience: self.audience,
-            capability_origin: self.capability_origin,
-            capability: self.capability,
-            valid_until,
+        // Verify that the chain isn't longer than allowed:
+        if i >= self.max_depth {
+            return Err(RcanError::ChainTooLong {
+                max_depth: self.max_depth,
+            });
+        }
+
+        // Verify proof chain issuer/audience integrity:
+        // The root must be issued by the authorizer, later proofs by any
+        // audience of their parent:
+        let issuer = &proof.payload.issuer;
+        let issued_by_target = match parent {
+            None => issuer == &cursor.issuer_target,
+            Some(parent) => parent.audience_is(issuer),
         };
+        if !issued_by_target {
+            return Err(RcanError::ProofIssuerMismatch {
+                index: i,
+                expected: S::key_to_bytes(&cursor.issuer_target),
+                found: S::key_to_bytes(issuer),
+            });
+        }
 
-        let to_sign = postcard::to_extend(&payload, DST.to_vec()).expect("vec");
-        let signature = self.issuer.sign(&to_sign);
+        // Verify each proof's time validity:
+        let expiry = &proof.payload.valid_until;
+        if !expiry.is_valid_at(now) {
+            return Err(RcanError::Expired {
+                index: i,
+                valid_until: expiry.clone(),
+            });
+        }
+        let valid_from = &proof.payload.valid_from;
+        if !valid_from.is_valid_at(now) {
+            return Err(RcanError::NotYetValid {
+                index: i,
+                valid_from: valid_from.clone(),
+            });
+        }
 
-        Rcan { signature, payload }
-    }
-}
+        // Verify that the proof isn't from before the issuance floor:
+        if let Some(floor) = self.issuance_floor {
+            let issued_at = proof.payload.issued_at;
+            if issued_at < floor {
+                return Err(RcanError::IssuedBeforeFloor {
+                    index: i,
+                    issued_at,
+                    floor,
+                });
+            }
+        }
 
-impl Expires {
-    pub fn valid_for(duration: Duration) -> Self {
-        Self::At(
-            SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .expect("now is after UNIX_EPOCH")
-                .add(duration)
-                .as_secs(),
+        // Verify that the proof hasn't been revoked:
+        if let Some(is_revoked) = &self.revocations {
+            if is_revoked(&proof.id()) {
+                return Err(RcanError::Revoked { index: i });
+            }
+        }
+
+        // Verify that the capability is actually reached through:
+        if proof.capability_issuer() != &self.identity {
+            return Err(RcanError::MissingDelegation {
+                index: i,
+                owner: S::key_to_bytes(&self.identity),
+            });
+        }
+
+        // Verify that no proof is re-delegated more often than it allows:
+        if let Some(limit) = cursor.delegation_limit {
+            if i > limit.last_index {
+                return Err(RcanError::DelegationDepthExceeded {
+                    index: limit.index,
+                    depth: limit.depth,
+                });
+            }
+        }
+        if let Some(depth) = proof.payload.max_delegation_depth {
+            let last_index = i + usize::from(depth);
+            if cursor
+                .delegation_limit
+                .is_none_or(|limit| last_index < limit.last_index)
+            {
+                cursor.delegation_limit = Some(DelegationLimit {
+                    index: i,
+                    depth,
+                    last_index,
+                });
+            }
+        }
+
+        // Verify that the proof is addressed to an audience its parent
+        // allows delegating to, and doesn't outlive it if required:
+        if let Some(parent) = parent {
+            if let Some(audience) = proof
+                .audiences()
+                .iter()
+                .find(|audience| !parent.allows_delegation_to(audience))
+            {
+                return Err(RcanError::AudienceNotAllowed {
+                    index: i,
+                    audience: S::key_to_bytes(audience),
+                });
+            }
+            if !parent.capabilities().iter().all(C::may_delegate) {
+                return Err(RcanError::NotDelegatable { index: i - 1 });
+            }
+            if self.monotonic_expiry && !expiry.is_within(parent.expires()) {
+                return Err(RcanError::OutlivesParent { index: i });
+            }
+        }
+
+        // Narrow the chain's validity window to this proof's:
+        let start = match proof.payload.valid_from {
+            NotBefore::At(from) => from,
+            NotBefore::Immediately => proof.payload.issued_at,
+        };
+        cursor.window = Some(match cursor.window.take() {
+            None => (start, expiry.clone()),
+            Some((latest_start, earliest_end)) => (
+                latest_start.max(start),
+                Expires::earliest([earliest_end, expiry.clone()]),
+            ),
+        });
+
+        // Continue checking the proof chain's integrity with this
+        // delegation's audience as the next issuer target:
+        cursor.issuer_target = *proof.audience();
+        cursor.index += 1;
+        Ok(())
+    }
+}
+
+impl Authorizer {
+    /// How many proofs a chain may have by default, see
+    /// [`Authorizer::with_max_depth`].
+    pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+    /// Verifies an invocation by whichever of several candidate `invokers`
+    /// the chain ends in, e.g. the device keys a client may authenticate
+    /// with, and returns it.
+    ///
+    /// Fails with an [`RcanError::NoMatchingInvoker`] error if the chain
+    /// doesn't end in any of them. Otherwise the invocation is checked
+    /// like in [`Authorizer::check_invocation_from`] for the first
+    /// matching invoker.
+    #[cfg(feature = "std")]
+    pub fn check_invocation_from_any_invoker<C: Capability>(
+        &self,
+        invokers: &[VerifyingKey],
+        capability: C,
+        proof_chain: &[&Rcan<C>],
+    ) -> Result<(VerifyingKey, InvocationProof)> {
+        let expected = proof_chain
+            .last()
+            .map_or(self.identity, |last| *last.audience());
+        let Some(invoker) = invokers.iter().find(|invoker| match proof_chain.last() {
+            Some(last) => last.audience_admits(invoker),
+            None => **invoker == self.identity,
+        }) else {
+            return Err(RcanError::NoMatchingInvoker {
+                expected: expected.as_bytes().to_vec(),
+            });
+        };
+        let proof = self.check_invocation_from(*invoker, capability, proof_chain)?;
+        Ok((*invoker, proof))
+    }
+
+    /// Verifies an invocation like [`Authorizer::check_invocation_from`],
+    /// and returns the proof link that granted it, e.g. for audit logs.
+    ///
+    /// Every proof has to permit the capability, the one granting it to
+    /// the `invoker` is the last one in the chain.
+    #[cfg(feature = "std")]
+    pub fn resolve_invocation_from<'a, C: Capability>(
+        &self,
+        invoker: VerifyingKey,
+        capability: C,
+        proof_chain: &[&'a Rcan<C>],
+    ) -> Result<ResolvedInvocation<'a, C>> {
+        let proof =
+            self.verify_invocation_at(SystemTime::now().into(), invoker, &capability, proof_chain)?;
+        let grant = proof_chain.last().map(|last| Grant {
+            index: proof_chain.len() - 1,
+            proof: last,
+            capability: last
+                .capabilities()
+                .iter()
+                .find(|granted| granted.permits(&capability))
+                .expect("checked"),
+        });
+        Ok(ResolvedInvocation { proof, grant })
+    }
+
+    /// Verifies an invocation of several capabilities at once, such as a
+    /// batched request.
+    ///
+    /// Succeeds only if the proof chain permits *every* capability in
+    /// `requested`. On failure [`RcanError::RequestNotPermitted`] names the
+    /// first denied capability, `requested[request]`, and the first proof
+    /// in the chain that doesn't permit it.
+    ///
+    /// See [`Authorizer::check_invocation_from`] for the requirements on
+    /// `invoker` and `proof_chain`.
+    #[cfg(feature = "std")]
+    pub fn check_invocation_from_all<C: Capability>(
+        &self,
+        invoker: VerifyingKey,
+        requested: &[C],
+        proof_chain: &[&Rcan<C>],
+    ) -> Result<InvocationProof> {
+        self.check_chain_integrity(SystemTime::now().into(), invoker, proof_chain)?;
+
+        for (i, capability) in requested.iter().enumerate() {
+            check_permitted(proof_chain, capability).map_err(|err| match err {
+                RcanError::CapabilityNotPermitted { proof } => {
+                    RcanError::RequestNotPermitted { request: i, proof }
+                }
+                err => err,
+            })?;
+        }
+
+        Ok(InvocationProof::for_chain(proof_chain))
+    }
+
+    /// Verifies an invocation like [`Authorizer::check_invocation_from`],
+    /// and additionally awaits a revocation lookup for every proof.
+    ///
+    /// The lookups only happen once the chain passed all other checks, one
+    /// link at a time, front to back.
+    #[cfg(feature = "async")]
+    #[cfg(feature = "std")]
+    pub async fn check_invocation_from_async<C: Capability>(
+        &self,
+        invoker: VerifyingKey,
+        capability: C,
+        proof_chain: &[&Rcan<C>],
+        revocations: impl AsyncRevocationSet<C>,
+    ) -> Result<InvocationProof> {
+        let proof = self.check_invocation_from(invoker, capability, proof_chain)?;
+
+        for (i, proof) in proof_chain.iter().enumerate() {
+            let revoked = revocations
+                .is_revoked(proof)
+                .await
+                .map_err(|source| RcanError::RevocationLookup { index: i, source })?;
+            if revoked {
+                return Err(RcanError::Revoked { index: i });
+            }
+        }
+
+        Ok(proof)
+    }
+
+    /// Verifies an invocation like [`Authorizer::check_invocation_from`],
+    /// and returns the [`Meta`] of the last proof in the chain along with
+    /// the proof of the invocation.
+    ///
+    /// The metadata is covered by the proof's signature, so it can be
+    /// trusted once the invocation is verified. It is `None` for an empty
+    /// chain, where the owner invokes its own capability.
+    #[cfg(feature = "std")]
+    pub fn check_invocation_from_with_meta<'a, C: Capability>(
+        &self,
+        invoker: VerifyingKey,
+        capability: C,
+        proof_chain: &[&'a Rcan<C>],
+    ) -> Result<(InvocationProof, Option<&'a Meta>)> {
+        let proof = self.check_invocation_from(invoker, capability, proof_chain)?;
+        Ok((proof, proof_chain.last().map(|proof| proof.meta())))
+    }
+
+    /// Verifies an invocation like [`Authorizer::check_invocation_from`],
+    /// and explains why each proof permits it.
+    ///
+    /// Returns one entry per proof, in order, with the proof's
+    /// [`Capability::permits_with_witness`].
+    #[cfg(feature = "std")]
+    pub fn explain_invocation<C: Capability>(
+        &self,
+        invoker: VerifyingKey,
+        capability: C,
+        proof_chain: &[&Rcan<C>],
+    ) -> Result<Vec<Option<Witness>>> {
+        self.check_chain_integrity(SystemTime::now().into(), invoker, proof_chain)?;
+
+        proof_chain
+            .iter()
+            .enumerate()
+            .map(|(i, proof)| {
+                let Some(granted) = proof
+                    .capabilities()
+                    .iter()
+                    .find(|granted| granted.permits(&capability))
+                else {
+                    return Err(RcanError::CapabilityNotPermitted { proof: i });
+                };
+                Ok(granted.permits_with_witness(&capability))
+            })
+            .collect()
+    }
+
+    /// Verifies an invocation like [`Authorizer::check_invocation_from`],
+    /// but lets `on_denied` pick a fallback when the capability is denied.
+    ///
+    /// `on_denied` is called at most once, with the requested capability and
+    /// the first capability of the first proof that denies it. If it returns
+    /// [`PolicyDecision::AllowDowngraded`], the replacement is checked
+    /// against the whole chain in place of the request, without consulting
+    /// `on_denied` again. Returns the capability that was authorized.
+    ///
+    /// # Security
+    ///
+    /// The callback decides what the caller gets to do instead of what it
+    /// asked for. Callers must act on the returned capability, never on the
+    /// original request, or a downgrade silently becomes an escalation.
+    /// The chain still has to permit the downgraded capability, so the
+    /// callback can't grant anything the delegations don't cover.
+    #[cfg(feature = "std")]
+    pub fn check_invocation_from_with_policy<C: Capability>(
+        &self,
+        invoker: VerifyingKey,
+        capability: C,
+        proof_chain: &[&Rcan<C>],
+        mut on_denied: impl FnMut(&C, &C) -> PolicyDecision<C>,
+    ) -> Result<C> {
+        self.check_chain_integrity(SystemTime::now().into(), invoker, proof_chain)?;
+
+        let Err(denied) = check_permitted(proof_chain, &capability) else {
+            return Ok(capability);
+        };
+        let RcanError::CapabilityNotPermitted { proof: i } = denied else {
+            return Err(denied);
+        };
+
+        match on_denied(&capability, proof_chain[i].capability()) {
+            PolicyDecision::Deny => Err(denied),
+            PolicyDecision::AllowDowngraded(downgraded) => {
+                check_permitted(proof_chain, &downgraded)?;
+                Ok(downgraded)
+            }
+        }
+    }
+
+    /// Verifies an invocation like [`Authorizer::check_invocation_from`],
+    /// but evaluates every proof with [`Capability::try_permits`] and
+    /// reports all denials and evaluation errors instead of the first one.
+    ///
+    /// Meant for debugging policies with external dependencies.
+    #[cfg(feature = "std")]
+    pub fn check_invocation_from_verbose<C: Capability>(
+        &self,
+        invoker: VerifyingKey,
+        capability: C,
+        proof_chain: &[&Rcan<C>],
+    ) -> InvocationReport {
+        let mut report = InvocationReport {
+            chain_error: self
+                .check_chain_integrity(SystemTime::now().into(), invoker, proof_chain)
+                .err(),
+            ..Default::default()
+        };
+        for (i, proof) in proof_chain.iter().enumerate() {
+            // Permitted if any capability permits, otherwise the first
+            // evaluation error counts.
+            let mut outcome = Ok(false);
+            for granted in proof.capabilities() {
+                match granted.try_permits(&capability) {
+                    Ok(true) => {
+                        outcome = Ok(true);
+                        break;
+                    }
+                    Ok(false) => {}
+                    Err(err) if outcome.is_ok() => outcome = Err(err),
+                    Err(_) => {}
+                }
+            }
+            match outcome {
+                Ok(true) => {}
+                Ok(false) => report.denied.push(i),
+                Err(err) => report.evaluation_errors.push((i, err)),
+            }
+        }
+        report
+    }
+
+    /// Verifies an invocation through a chain rooted on a rotated-out key.
+    ///
+    /// `chain`'s cross-signing token must be issued by this authorizer, so
+    /// this is the authorizer for the *new* root key. The old chain itself
+    /// is checked as if the old root were the authorizer, and the
+    /// `capability` must also be permitted by the cross-signing token.
+    #[cfg(feature = "std")]
+    pub fn check_reanchored_invocation_from<C: Capability>(
+        &self,
+        invoker: VerifyingKey,
+        capability: C,
+        chain: &ReanchoredChain<'_, C>,
+    ) -> Result<InvocationProof> {
+        let cross_sign = chain.cross_sign;
+        self.check_chain_integrity(
+            SystemTime::now().into(),
+            *cross_sign.audience(),
+            &[cross_sign],
+        )?;
+        if !cross_sign.permits(&capability) {
+            return Err(RcanError::CrossSignNotPermitted);
+        }
+
+        let old_root = Authorizer {
+            identity: *cross_sign.audience(),
+            ..self.clone()
+        };
+        let proof = old_root.check_invocation_from(invoker, capability, chain.proof_chain)?;
+        Ok(InvocationProof {
+            expires: Expires::earliest([proof.expires, cross_sign.expires().clone()]),
+        })
+    }
+
+    /// Computes the combined authority `invoker` holds through two proof
+    /// chains: the intersection of their
+    /// [effective capabilities](Authorizer::effective_capability), or
+    /// `None` if they are disjoint or either can't be expressed as a single
+    /// capability.
+    ///
+    /// Both chains are verified like in
+    /// [`Authorizer::check_invocation_from`], and their effective
+    /// capabilities combined with [`Capability::meet`]. An empty chain,
+    /// valid only if `invoker` is this authorizer's identity, doesn't
+    /// restrict the result, but both chains can't be empty.
+    #[cfg(feature = "std")]
+    pub fn effective_capability_intersection<C: Capability + Clone>(
+        &self,
+        invoker: VerifyingKey,
+        chain_a: &[&Rcan<C>],
+        chain_b: &[&Rcan<C>],
+    ) -> Result<Option<C>> {
+        // `None` for an empty chain, which doesn't restrict the result.
+        let effective = |chain: &[&Rcan<C>]| {
+            if chain.is_empty() {
+                self.check_chain_integrity(SystemTime::now().into(), invoker, chain)?;
+                return Ok(None);
+            }
+            self.effective_capability(invoker, chain).map(Some)
+        };
+        match (effective(chain_a)?, effective(chain_b)?) {
+            (None, None) => Err(RcanError::NothingToIntersect),
+            (Some(effective), None) | (None, Some(effective)) => Ok(effective),
+            (Some(a), Some(b)) => Ok(a.zip(b).and_then(|(a, b)| a.meet(&b))),
+        }
+    }
+
+    /// The strongest capability `invoker` holds through `proof_chain`, the
+    /// answer to what it can do rather than whether it can do one thing.
+    ///
+    /// The chain is verified like in [`Authorizer::check_invocation_from`].
+    /// Each capability of the last proof is attenuated by every proof
+    /// before it with [`Capability::meet`], and the results are merged
+    /// with [`Capability::join`]. `None` if that can't be expressed as a
+    /// single capability, e.g. because the default `join` can't merge two
+    /// unrelated capabilities. Fails with [`RcanError::EmptyChain`] for an
+    /// empty chain, whose invoker holds everything.
+    #[cfg(feature = "std")]
+    pub fn effective_capability<C: Capability + Clone>(
+        &self,
+        invoker: VerifyingKey,
+        proof_chain: &[&Rcan<C>],
+    ) -> Result<Option<C>> {
+        self.check_chain_integrity(SystemTime::now().into(), invoker, proof_chain)?;
+        let Some((tip, proofs)) = proof_chain.split_last() else {
+            return Err(RcanError::EmptyChain);
+        };
+        Ok(join_all(
+            tip.capabilities()
+                .iter()
+                .filter_map(|capability| attenuate(capability, proofs)),
+        ))
+    }
+}
+
+/// Where [`Authorizer::check_next_proof`] is in a chain, so chains can be
+/// checked one proof at a time.
+struct ChainCursor<S: SignatureScheme> {
+    /// Index of the next proof.
+    index: usize,
+    /// Who the next proof must be issued by.
+    issuer_target: S::VerifyingKey,
+    /// The tightest delegation depth limit so far.
+    delegation_limit: Option<DelegationLimit>,
+    /// The latest start and earliest expiry so far, `None` before the
+    /// first proof.
+    window: Option<(u64, Expires)>,
+    /// See [`Authorizer::with_max_window`].
+    max_window: Option<Duration>,
+}
+
+#[derive(Clone, Copy)]
+struct DelegationLimit {
+    /// Index of the proof setting the limit.
+    index: usize,
+    depth: u8,
+    /// Index of the last proof the limit allows.
+    last_index: usize,
+}
+
+impl<S: SignatureScheme> ChainCursor<S> {
+    fn new(authorizer: &Authorizer<S>) -> Self {
+        Self {
+            index: 0,
+            issuer_target: authorizer.identity,
+            delegation_limit: None,
+            window: None,
+            max_window: authorizer.max_window,
+        }
+    }
+
+    /// Verifies that a chain ending in `last` ends in `invoker`, and that
+    /// its validity window isn't too long.
+    fn check_end<C>(&self, invoker: S::VerifyingKey, last: Option<&Rcan<C, S>>) -> Result<()> {
+        // The invoker may also be another audience of the last proof, or a
+        // member of its group audience:
+        let invoker_is_member = last.is_some_and(|proof| proof.audience_admits(&invoker));
+        if invoker != self.issuer_target && !invoker_is_member {
+            return Err(RcanError::ChainEndpointMismatch {
+                expected: S::key_to_bytes(&self.issuer_target),
+                found: S::key_to_bytes(&invoker),
+            });
+        }
+
+        if let (Some(max_window), Some((start, end))) = (self.max_window, &self.window) {
+            let within = match end {
+                Expires::At(end) => Duration::from_secs(end.saturating_sub(*start)) <= max_window,
+                Expires::Never | Expires::AtBlock(_) => false,
+            };
+            if !within {
+                return Err(RcanError::ValidityWindowExceeded { max_window });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Evidence that an invocation was verified, returned by the
+/// [`Authorizer`] checks.
+///
+/// Only the authorizer can create one, so actions that take an
+/// `&InvocationProof` can't run without a successful check. Dropping it
+/// unused means the check's outcome was ignored:
+///
+/// ```compile_fail
+/// #![deny(unused_must_use)]
+/// # use rcan::{caps::EpochCapability, Authorizer, SigningKey};
+/// # let service = SigningKey::from_bytes(&[0u8; 32]).verifying_key();
+/// # let authorizer = Authorizer::new(service);
+/// authorizer
+///     .check_invocation_from(service, EpochCapability(1), &[])
+///     .unwrap();
+/// ```
+#[must_use = "an invocation is only verified if the check succeeded"]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvocationProof {
+    expires: Expires,
+}
+
+impl InvocationProof {
+    fn for_chain<C, S: SignatureScheme>(proof_chain: &[&Rcan<C, S>]) -> Self {
+        Self {
+            expires: Expires::earliest(proof_chain.iter().map(|proof| proof.expires().clone())),
+        }
+    }
+
+    /// When the verified chain expires, the earliest expiry of its proofs.
+    pub fn expires(&self) -> &Expires {
+        &self.expires
+    }
+}
+
+/// An invocation verified by [`Authorizer::resolve_invocation_from`].
+#[must_use = "an invocation is only verified if the check succeeded"]
+#[derive(Debug)]
+pub struct ResolvedInvocation<'a, C> {
+    /// Evidence that the invocation was verified.
+    pub proof: InvocationProof,
+    /// The proof link that granted the capability, or `None` if the chain
+    /// is empty and the owner invoked its own capability.
+    pub grant: Option<Grant<'a, C>>,
+}
+
+/// The proof link that granted an invocation, see [`ResolvedInvocation`].
+#[derive(Debug)]
+pub struct Grant<'a, C> {
+    /// Position of the proof in the chain.
+    pub index: usize,
+    /// The proof.
+    pub proof: &'a Rcan<C>,
+    /// The proof's capability that permitted the invocation.
+    pub capability: &'a C,
+}
+
+/// An [`Authorizer`] pinned to a single capability type.
+///
+/// Services sharing one identity across several schemas can hand out a
+/// `TypedAuthorizer` per schema, so invocations can't be checked against
+/// the wrong capability type by accident:
+///
+/// ```compile_fail
+/// use ed25519_dalek::SigningKey;
+/// use rcan::{caps::EpochCapability, Expires, Rcan, TypedAuthorizer};
+///
+/// let service = SigningKey::from_bytes(&[0u8; 32]);
+/// let authorizer = TypedAuthorizer::<EpochCapability>::new(service.verifying_key());
+/// let rcan = Rcan::issuing_builder(&service, service.verifying_key(), 1u8).sign(Expires::Never);
+/// authorizer.check_invocation_from(service.verifying_key(), 1u8, &[&rcan]);
+/// ```
+pub struct TypedAuthorizer<C> {
+    authorizer: Authorizer,
+    _capability: core::marker::PhantomData<fn() -> C>,
+}
+
+impl<C> Clone for TypedAuthorizer<C> {
+    fn clone(&self) -> Self {
+        Self {
+            authorizer: self.authorizer.clone(),
+            _capability: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<C: Capability> TypedAuthorizer<C> {
+    /// Constructs a new authorizer for given identity, accepting only
+    /// capabilities of type `C`.
+    pub fn new(identity: VerifyingKey) -> Self {
+        Authorizer::new(identity).into()
+    }
+
+    /// The untyped authorizer, for the checks not mirrored here.
+    pub fn authorizer(&self) -> &Authorizer {
+        &self.authorizer
+    }
+
+    /// See [`Authorizer::check_invocation_from`].
+    #[cfg(feature = "std")]
+    pub fn check_invocation_from(
+        &self,
+        invoker: VerifyingKey,
+        capability: C,
+        proof_chain: &[&Rcan<C>],
+    ) -> Result<InvocationProof> {
+        self.authorizer
+            .check_invocation_from(invoker, capability, proof_chain)
+    }
+}
+
+impl<C> From<Authorizer> for TypedAuthorizer<C> {
+    fn from(authorizer: Authorizer) -> Self {
+        Self {
+            authorizer,
+            _capability: core::marker::PhantomData,
+        }
+    }
+}
+
+/// What [`Authorizer::check_invocation_from_with_policy`] should do about a
+/// denied capability.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyDecision<C> {
+    /// Fail the invocation.
+    Deny,
+    /// Authorize this capability instead, if the chain permits it.
+    AllowDowngraded(C),
+}
+
+/// The outcome of [`Authorizer::check_invocation_from_verbose`].
+///
+/// Unlike the other checks, which stop at the first problem, this collects
+/// every denial and evaluation error across the chain.
+#[derive(Debug, Default)]
+pub struct InvocationReport {
+    /// Why the chain itself is broken, independent of the capability, if it is.
+    pub chain_error: Option<RcanError>,
+    /// Indices of the proofs whose capability doesn't permit the request.
+    pub denied: Vec<usize>,
+    /// Proofs whose [`Capability::try_permits`] failed, by index.
+    pub evaluation_errors: Vec<(usize, BoxError)>,
+}
+
+impl InvocationReport {
+    /// Whether the invocation is authorized.
+    pub fn is_permitted(&self) -> bool {
+        self.chain_error.is_none() && self.denied.is_empty() && self.evaluation_errors.is_empty()
+    }
+}
+
+/// A proof chain rooted on an old root key, re-anchored onto a new one.
+///
+/// When a service rotates its root key, chains rooted on the old key stop
+/// verifying under an [`Authorizer`] for the new key. To keep them working,
+/// the new root signs a cross-signing token: an issuing token from the new
+/// root to the old root's public key, granting the authority it vouches
+/// for. Verify the result with
+/// [`Authorizer::check_reanchored_invocation_from`].
+#[derive(Debug)]
+pub struct ReanchoredChain<'a, C> {
+    cross_sign: &'a Rcan<C>,
+    // Only checked against the system clock.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    proof_chain: &'a [&'a Rcan<C>],
+}
+
+impl<'a, C> ReanchoredChain<'a, C> {
+    /// Re-anchors `old_chain` under the issuer of `cross_sign`.
+    ///
+    /// Fails if `cross_sign` isn't an issuing token addressed to the root
+    /// of `old_chain`.
+    pub fn new(cross_sign: &'a Rcan<C>, old_chain: &'a [&'a Rcan<C>]) -> Result<Self> {
+        if cross_sign.capability_origin() != &CapabilityOrigin::Issuer {
+            return Err(RcanError::RootIsDelegation {
+                owner: cross_sign.capability_issuer().as_bytes().to_vec(),
+            });
+        }
+        let Some(first) = old_chain.first() else {
+            return Err(RcanError::EmptyChain);
+        };
+        if first.capability_issuer() != cross_sign.audience() {
+            return Err(RcanError::IssuerMismatch {
+                expected: cross_sign.audience().as_bytes().to_vec(),
+                found: first.capability_issuer().as_bytes().to_vec(),
+            });
+        }
+        Ok(Self {
+            cross_sign,
+            proof_chain: old_chain,
+        })
+    }
+
+    /// The new root key this chain is anchored on.
+    pub fn root(&self) -> &VerifyingKey {
+        self.cross_sign.issuer()
+    }
+}
+
+/// The identifier of a token, see [`Rcan::id`].
+///
+/// Displays and parses as lowercase hex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RcanId([u8; 32]);
+
+impl RcanId {
+    /// The bytes of the identifier.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl core::fmt::Display for RcanId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl core::str::FromStr for RcanId {
+    type Err = RcanError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(s, &mut bytes)
+            .map_err(|err| RcanError::Malformed(format!("invalid token id: {err}")))?;
+        Ok(Self(bytes))
+    }
+}
+
+/// A summary of a token for inspection tools, see [`Rcan::summarize`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RcanSummary {
+    /// The wire format version the token encodes with.
+    pub version: u8,
+    /// The issuer's key, as hex.
+    pub issuer_hex: String,
+    /// The audience's key, as hex.
+    pub audience_hex: String,
+    /// Where the capability comes from.
+    pub origin: CapabilityOrigin,
+    /// The capabilities, formatted with [`Debug`](core::fmt::Debug).
+    pub capability_debug: String,
+    /// When the token expires.
+    pub expires: Expires,
+    /// Whether the signature verifies and the token is valid at the time
+    /// it was summarized.
+    pub valid: bool,
+    /// The token's identifier.
+    pub id: RcanId,
+}
+
+/// A token for attenuated capability delegations, signed with the
+/// [`SignatureScheme`] `S`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rcan<C, S: SignatureScheme = Ed25519> {
+    /// The actual content.
+    pub payload: Payload<C, S>,
+    /// Signature over the serialized payload.
+    pub signature: S::Signature,
+}
+
+/// Orders tokens soonest-expiring first, then by issuer and audience
+/// bytes, so a sorted collection can drive renewal scheduling.
+///
+/// Ties are broken by the signature bytes, to stay consistent with `Eq`.
+impl<C: Eq> Ord for Rcan<C> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.expires()
+            .cmp(other.expires())
+            .then_with(|| self.issuer().as_bytes().cmp(other.issuer().as_bytes()))
+            .then_with(|| self.audience().as_bytes().cmp(other.audience().as_bytes()))
+            .then_with(|| self.signature.to_bytes().cmp(&other.signature.to_bytes()))
+    }
+}
+
+impl<C: Eq> PartialOrd for Rcan<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A one-line summary for humans, like
+/// `rcan[ iss=ed25519:3b6a27bc… aud=ed25519:8a88e3dd… cap=ReadWrite origin=Issuer expires=never ]`.
+///
+/// Keys are shortened to their first four bytes, and the signature isn't
+/// shown. Several capabilities or audiences are separated by commas. Use
+/// [`Rcan::summarize`] for the full keys.
+impl<C: core::fmt::Display> core::fmt::Display for Rcan<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "rcan[ iss={} aud=", ShortKey(self.issuer()))?;
+        for (i, audience) in self.audiences().iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(f, "{sep}{}", ShortKey(audience))?;
+        }
+        f.write_str(" cap=")?;
+        for (i, capability) in self.capabilities().iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(f, "{sep}{capability}")?;
+        }
+        f.write_str(" origin=")?;
+        match self.capability_origin() {
+            CapabilityOrigin::Issuer => f.write_str("Issuer")?,
+            CapabilityOrigin::Delegation(owner) => write!(f, "Delegation({})", ShortKey(owner))?,
+        }
+        write!(f, " expires={} ]", self.expires())
+    }
+}
+
+/// A key shortened for display.
+struct ShortKey<'a>(&'a VerifyingKey);
+
+impl core::fmt::Display for ShortKey<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ed25519:{}…", hex::encode(&self.0.as_bytes()[..4]))
+    }
+}
+
+impl<C: Serialize, S: SignatureScheme> Serialize for Rcan<C, S> {
+    fn serialize<Ser>(&self, serializer: Ser) -> core::result::Result<Ser::Ok, Ser::Error>
+    where
+        Ser: serde::Serializer,
+    {
+        use serde::ser::SerializeTuple;
+        let mut tup = serializer.serialize_tuple(2)?;
+        tup.serialize_element(&self.payload)?;
+        tup.serialize_element(&SignatureWire::<S>(self.signature))?;
+        tup.end()
+    }
+}
+
+impl<'de, C: Deserialize<'de> + Serialize, S: SignatureScheme> Deserialize<'de> for Rcan<C, S> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let UnverifiedRcan(rcan) = UnverifiedRcan::deserialize(deserializer)?;
+
+        // Verify before yielding, so a deserialized `Rcan` is
+        // always signature checked. Without this, serde wire
+        // formats hand back an unverified token while only
+        // `decode` checks the signature.
+        rcan.verify_signature().map_err(serde::de::Error::custom)?;
+
+        Ok(rcan)
+    }
+}
+
+/// An [`Rcan`] deserialized without checking its signature, for decoding
+/// paths that verify it differently. Never hand one of these out.
+struct UnverifiedRcan<C, S: SignatureScheme = Ed25519>(Rcan<C, S>);
+
+impl<'de, C: Deserialize<'de>, S: SignatureScheme> Deserialize<'de> for UnverifiedRcan<C, S> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct RcanVisitor<C, S>(core::marker::PhantomData<(C, S)>);
+
+        impl<'de, C: Deserialize<'de>, S: SignatureScheme> serde::de::Visitor<'de> for RcanVisitor<C, S> {
+            type Value = UnverifiedRcan<C, S>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("an rcan token (payload, signature)")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> core::result::Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let payload: Payload<C, S> = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let SignatureWire(signature) = seq
+                    .next_element::<SignatureWire<S>>()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                Ok(UnverifiedRcan(Rcan { payload, signature }))
+            }
+        }
+
+        deserializer.deserialize_tuple(2, RcanVisitor::<C, S>(core::marker::PhantomData))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, derive_more::Debug, PartialEq, Eq)]
+#[serde(bound(serialize = "C: Serialize", deserialize = "C: Deserialize<'de>"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(bound = "C: schemars::JsonSchema"))]
+pub struct Payload<C, S: SignatureScheme = Ed25519> {
+    /// The issuer
+    #[debug("{}", hex::encode(S::key_to_bytes(issuer)))]
+    #[serde(
+        serialize_with = "verifying_key_serde::serialize::<S, _>",
+        deserialize_with = "verifying_key_serde::deserialize::<S, _>"
+    )]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    issuer: S::VerifyingKey,
+    /// The intended audiences, at least one and none repeated
+    #[debug("{:?}", audiences.iter().map(|key| hex::encode(S::key_to_bytes(key))).collect::<Vec<_>>())]
+    #[serde(
+        serialize_with = "verifying_keys_serde::serialize::<S, _>",
+        deserialize_with = "deserialize_audiences::<S, _>"
+    )]
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<String>"))]
+    audiences: Vec<S::VerifyingKey>,
+    /// The origin of the capability
+    capability_origin: CapabilityOrigin<S>,
+    /// The capabilities, at least one
+    #[serde(
+        deserialize_with = "deserialize_capabilities",
+        bound(deserialize = "C: Deserialize<'de>")
+    )]
+    capabilities: Vec<C>,
+    /// Valid until unix timestamp in seconds.
+    valid_until: Expires,
+    /// Valid from unix timestamp in seconds.
+    valid_from: NotBefore,
+    /// Unix timestamp in seconds of when the token was signed.
+    issued_at: u64,
+    /// How many more times the capability may be re-delegated after this
+    /// token, if limited.
+    max_delegation_depth: Option<u8>,
+    /// Metadata that doesn't confer authority.
+    meta: Meta,
+    /// Members of a group audience, each of which may invoke on the
+    /// group's behalf. Empty unless the audience is a [`GroupKey`], see
+    /// [`Rcan::audiences`].
+    #[debug("{:?}", audience_members.iter().map(|key| hex::encode(S::key_to_bytes(key))).collect::<Vec<_>>())]
+    #[serde(
+        serialize_with = "verifying_keys_serde::serialize::<S, _>",
+        deserialize_with = "verifying_keys_serde::deserialize::<S, _>"
+    )]
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<String>"))]
+    audience_members: Vec<S::VerifyingKey>,
+    /// The only audiences the next delegation may be addressed to, if
+    /// restricted.
+    #[debug("{:?}", delegation_audience_allowlist.as_ref().map(|keys| keys.iter().map(|key| hex::encode(S::key_to_bytes(key))).collect::<Vec<_>>()))]
+    #[serde(
+        serialize_with = "optional_verifying_keys_serde::serialize::<S, _>",
+        deserialize_with = "optional_verifying_keys_serde::deserialize::<S, _>"
+    )]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<Vec<String>>"))]
+    delegation_audience_allowlist: Option<Vec<S::VerifyingKey>>,
+}
+
+/// A group of keys addressed as one audience, like the members of a
+/// threshold group.
+///
+/// `key` is the group's own key. It is the token's audience, so further
+/// delegations must be issued by it. Invocations are also accepted from
+/// any of the `members`, see [`RcanBuilder::audience_group`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupKey<S: SignatureScheme = Ed25519> {
+    /// The group's own key.
+    pub key: S::VerifyingKey,
+    /// The keys that may invoke on the group's behalf.
+    pub members: Vec<S::VerifyingKey>,
+}
+
+/// Signed metadata carried by a token, like request ids or correlation
+/// tags. Covered by the signature, but ignored by every authorization check.
+///
+/// Values are plain strings, since postcard can't encode self-describing
+/// values like JSON. Structured data can be stored serialized, e.g. as a
+/// JSON string.
+pub type Meta = BTreeMap<String, String>;
+
+/// Deserializes the capabilities of a [`Payload`], which can't be empty.
+fn deserialize_capabilities<'de, D, C>(deserializer: D) -> core::result::Result<Vec<C>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    C: Deserialize<'de>,
+{
+    let capabilities = Vec::<C>::deserialize(deserializer)?;
+    if capabilities.is_empty() {
+        return Err(serde::de::Error::invalid_length(
+            0,
+            &"at least one capability",
+        ));
+    }
+    Ok(capabilities)
+}
+
+/// Deserializes the audiences of a [`Payload`], see [`check_audiences`].
+fn deserialize_audiences<'de, S, D>(
+    deserializer: D,
+) -> core::result::Result<Vec<S::VerifyingKey>, D::Error>
+where
+    S: SignatureScheme,
+    D: serde::Deserializer<'de>,
+{
+    let audiences = verifying_keys_serde::deserialize::<S, _>(deserializer)?;
+    check_audiences(&audiences).map_err(serde::de::Error::custom)?;
+    Ok(audiences)
+}
+
+/// Checks that a token has at least one audience, and none twice.
+fn check_audiences<K: PartialEq>(audiences: &[K]) -> core::result::Result<(), &'static str> {
+    if audiences.is_empty() {
+        return Err("token without audiences");
+    }
+    if (1..audiences.len()).any(|i| audiences[..i].contains(&audiences[i])) {
+        return Err("token with a repeated audience");
+    }
+    Ok(())
+}
+
+impl<C, S: SignatureScheme> Payload<C, S> {
+    /// The key the payload is signed by.
+    pub fn issuer(&self) -> &S::VerifyingKey {
+        &self.issuer
+    }
+
+    /// The bytes the issuer signs for the payload, `DST ++ postcard(payload)`.
+    ///
+    /// These are rebuilt from the payload alone, not taken from the token's
+    /// encoding, so a verifier holding just the payload can check a
+    /// signature made elsewhere, see [`Rcan::verify_payload_signature`].
+    /// They only change with the wire format [`VERSION`].
+    pub fn signing_bytes(&self) -> Vec<u8>
+    where
+        C: Serialize,
+    {
+        postcard::to_extend(self, DST.to_vec()).expect("vec")
+    }
+
+    /// The first capability, see [`Payload::capabilities`].
+    pub fn capability(&self) -> &C {
+        &self.capabilities[0]
+    }
+
+    /// All capabilities granted, never empty.
+    pub fn capabilities(&self) -> &[C] {
+        &self.capabilities
+    }
+
+    /// The signed metadata, see [`Meta`].
+    pub fn meta(&self) -> &Meta {
+        &self.meta
+    }
+
+    pub fn capability_origin(&self) -> &CapabilityOrigin<S> {
+        &self.capability_origin
+    }
+}
+
+/// The potential origins of a capability.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(bound = "")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(bound = ""))]
+pub enum CapabilityOrigin<S: SignatureScheme = Ed25519> {
+    /// The origin is the issuer itself
+    Issuer,
+    /// This is a delegation, with this key being the root of the delegation chain.
+    Delegation(
+        #[serde(
+            serialize_with = "verifying_key_serde::serialize::<S, _>",
+            deserialize_with = "verifying_key_serde::deserialize::<S, _>"
+        )]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+        S::VerifyingKey,
+    ),
+}
+
+/// When an rcan expires
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, derive_more::Display)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Expires {
+    /// Never expires
+    #[display("never")]
+    Never,
+    /// Valid until given unix timestamp in seconds
+    #[display("{_0}")]
+    At(u64),
+    /// Valid up to and including the given block height, for ledgers that
+    /// keep time in blocks
+    #[display("block {_0}")]
+    AtBlock(u64),
+}
+
+/// The current position on a clock, to check [`Expires`] and
+/// [`NotBefore`] against.
+///
+/// Wall-clock and block-height validity use different clocks, so a bound
+/// on one clock is never satisfied at a position on the other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Position {
+    /// A point in wall-clock time, in unix seconds
+    Time(u64),
+    /// A block height
+    Block(u64),
+}
+
+#[cfg(feature = "std")]
+impl From<SystemTime> for Position {
+    fn from(time: SystemTime) -> Self {
+        Position::Time(unix_secs(time))
+    }
+}
+
+/// When an rcan becomes valid
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq, derive_more::Display)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum NotBefore {
+    /// Valid as soon as it is signed
+    #[default]
+    #[display("immediately")]
+    Immediately,
+    /// Valid from given unix timestamp in seconds
+    #[display("{_0}")]
+    At(u64),
+}
+
+/// A token of the [`SignatureScheme`] `S` to be signed by an issuer of type
+/// `I`, a [`SigningKey`] unless the builder was created with another
+/// [`Issuer`].
+pub struct RcanBuilder<'s, C, I: ?Sized = SigningKey, S: SignatureScheme = Ed25519> {
+    issuer: &'s I,
+    audience: S::VerifyingKey,
+    capability_origin: CapabilityOrigin<S>,
+    capabilities: Vec<C>,
+    max_delegation_depth: Option<u8>,
+    meta: Meta,
+    audience_members: Vec<S::VerifyingKey>,
+    delegation_audience_allowlist: Option<Vec<S::VerifyingKey>>,
+    /// Audiences besides `audience`.
+    additional_audiences: Vec<S::VerifyingKey>,
+    /// In unix seconds.
+    issued_at: Option<u64>,
+    valid_from: NotBefore,
+}
+
+impl<C, S: SignatureScheme> Rcan<C, S> {
+    pub fn issuing_builder<I: Issuer<S> + ?Sized>(
+        issuer: &I,
+        audience: S::VerifyingKey,
+        capability: C,
+    ) -> RcanBuilder<'_, C, I, S> {
+        RcanBuilder {
+            issuer,
+            audience,
+            capability_origin: CapabilityOrigin::Issuer,
+            capabilities: vec![capability],
+            max_delegation_depth: None,
+            meta: Meta::new(),
+            audience_members: Vec::new(),
+            delegation_audience_allowlist: None,
+            additional_audiences: Vec::new(),
+            issued_at: None,
+            valid_from: NotBefore::Immediately,
+        }
+    }
+
+    pub fn delegating_builder<I: Issuer<S> + ?Sized>(
+        issuer: &I,
+        audience: S::VerifyingKey,
+        owner: S::VerifyingKey,
+        capability: C,
+    ) -> RcanBuilder<'_, C, I, S> {
+        RcanBuilder {
+            issuer,
+            audience,
+            capability_origin: CapabilityOrigin::Delegation(owner),
+            capabilities: vec![capability],
+            max_delegation_depth: None,
+            meta: Meta::new(),
+            audience_members: Vec::new(),
+            delegation_audience_allowlist: None,
+            additional_audiences: Vec::new(),
+            issued_at: None,
+            valid_from: NotBefore::Immediately,
+        }
+    }
+
+    /// A [`Rcan::delegating_builder`] for the link following `parent`:
+    /// the owner is `parent`'s capability issuer, and the delegation depth
+    /// is one less than `parent`'s, if it limits it.
+    ///
+    /// Chaining delegations through this keeps the remaining depth visible
+    /// at every link. A `parent` with no delegations left yields a depth
+    /// of 0, the chain is rejected either way.
+    pub fn delegating_builder_from<'s, I: Issuer<S> + ?Sized>(
+        issuer: &'s I,
+        audience: S::VerifyingKey,
+        parent: &Rcan<C, S>,
+        capability: C,
+    ) -> RcanBuilder<'s, C, I, S> {
+        let mut builder =
+            Self::delegating_builder(issuer, audience, *parent.capability_issuer(), capability);
+        builder.max_delegation_depth = parent
+            .remaining_delegations_possible()
+            .map(|depth| depth.saturating_sub(1));
+        builder
+    }
+
+    /// Signs `payload`, which must have `issuer` as its issuer.
+    fn sign_payload(issuer: &S::SigningKey, payload: Payload<C, S>) -> Self
+    where
+        C: Serialize,
+    {
+        debug_assert_eq!(payload.issuer, S::verifying_key(issuer));
+        let to_sign = signing_preimage(&payload, None);
+        let signature = S::sign(issuer, &to_sign);
+
+        Rcan { signature, payload }
+    }
+
+    /// Re-issues the same grant signed by `new_issuer`, optionally with a
+    /// new expiry, for migrating to a new issuer key.
+    ///
+    /// Audience, capabilities, their origin, expiry, activation time,
+    /// delegation depth and metadata are kept, the issuance time is now.
+    /// The issuer changes though, so chains containing this token have to
+    /// be rebuilt: the previous link must be addressed to `new_issuer`, and
+    /// an issuing token now grants `new_issuer`'s capability instead of the
+    /// old issuer's.
+    pub fn resign_as(&self, new_issuer: &S::SigningKey, valid_until: Option<Expires>) -> Rcan<C, S>
+    where
+        C: Clone + Serialize,
+    {
+        let valid_until = valid_until.unwrap_or_else(|| self.payload.valid_until.clone());
+        self.to_builder(new_issuer).sign(valid_until)
+    }
+
+    /// Returns an unsigned builder pre-filled with this token's audience,
+    /// capabilities, origin, activation time, delegation depth and
+    /// metadata, to tweak and [`RcanBuilder::sign`] again with a new
+    /// expiry. The new token is issued when it's signed.
+    ///
+    /// The new token gets a fresh signature by `issuer`, which may differ
+    /// from this token's issuer. See [`Rcan::resign_as`] for what that
+    /// means for chains.
+    pub fn to_builder<'s, I: ?Sized>(&self, issuer: &'s I) -> RcanBuilder<'s, C, I, S>
+    where
+        C: Clone,
+    {
+        RcanBuilder {
+            issuer,
+            audience: *self.audience(),
+            capability_origin: self.payload.capability_origin.clone(),
+            capabilities: self.payload.capabilities.clone(),
+            max_delegation_depth: self.payload.max_delegation_depth,
+            meta: self.payload.meta.clone(),
+            audience_members: self.payload.audience_members.clone(),
+            delegation_audience_allowlist: self.payload.delegation_audience_allowlist.clone(),
+            additional_audiences: self.audiences()[1..].to_vec(),
+            issued_at: None,
+            valid_from: self.payload.valid_from.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8>
+    where
+        C: Serialize,
+    {
+        postcard::to_extend(self, vec![VERSION]).expect("vec")
+    }
+
+    /// A stable identifier of the token, e.g. for caching or to revoke it
+    /// with [`Authorizer::with_revocations`].
+    ///
+    /// The BLAKE3 hash of [`Rcan::encode`], signature included. Decoding
+    /// and re-encoding a token doesn't change its id, since the encoding
+    /// is canonical.
+    pub fn id(&self) -> RcanId
+    where
+        C: Serialize,
+    {
+        RcanId(*blake3::hash(&self.encode()).as_bytes())
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let Some(version) = bytes.first() else {
+            return Err(RcanError::EmptyToken);
+        };
+        if *version != VERSION {
+            return Err(RcanError::UnsupportedVersion(*version));
+        }
+        // `Rcan`'s `Deserialize` verifies the signature, so a successful
+        // decode is already signature-checked.
+        let rcan: Self = postcard::from_bytes(&bytes[1..])?;
+        Ok(rcan)
+    }
+
+    /// Like [`Rcan::decode`], but also rejects tokens that aren't in
+    /// canonical form, so every token has a single byte representation.
+    ///
+    /// That is:
+    ///
+    /// - bytes that don't re-encode to themselves, like varints that
+    ///   aren't minimally encoded or trailing bytes. The signature covers
+    ///   the re-encoded payload, so these would verify as the same token.
+    /// - a [`CapabilityOrigin::Delegation`] naming the token's own issuer,
+    ///   which means the same as [`CapabilityOrigin::Issuer`] and is never
+    ///   produced by [`RcanBuilder`].
+    pub fn decode_strict(bytes: &[u8]) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let rcan = Self::decode(bytes)?;
+        if rcan.encode() != bytes
+            || rcan.payload.capability_origin == CapabilityOrigin::Delegation(rcan.payload.issuer)
+        {
+            return Err(RcanError::NonCanonical);
+        }
+        Ok(rcan)
+    }
+
+    /// Decodes a token signed with [`RcanBuilder::sign_with_aad`],
+    /// verifying its signature against the same associated data.
+    ///
+    /// Fails if the token was signed for different associated data, or
+    /// without any.
+    pub fn decode_with_aad(bytes: &[u8], aad: &[u8]) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let Some(version) = bytes.first() else {
+            return Err(RcanError::EmptyToken);
+        };
+        if *version != VERSION {
+            return Err(RcanError::UnsupportedVersion(*version));
+        }
+        let UnverifiedRcan(rcan) = postcard::from_bytes::<UnverifiedRcan<C, S>>(&bytes[1..])?;
+        rcan.verify_signature_with_aad(aad)?;
+        Ok(rcan)
+    }
+
+    /// Verify the signature over the payload. The signed bytes are
+    /// `DST ++ postcard(payload)`, matching [`RcanBuilder::sign`].
+    fn verify_signature(&self) -> Result<()>
+    where
+        C: Serialize,
+    {
+        Self::verify_payload_signature(&self.payload, &self.signature)
+    }
+
+    /// Verifies that `signature` is the payload's issuer's signature over
+    /// its [signing bytes](Payload::signing_bytes).
+    ///
+    /// For verifiers that receive payload and signature apart from each
+    /// other, e.g. from a separate signing service. Use
+    /// [`Rcan::from_signed_parts`] to assemble them into a token.
+    pub fn verify_payload_signature(payload: &Payload<C, S>, signature: &S::Signature) -> Result<()>
+    where
+        C: Serialize,
+    {
+        S::verify(&payload.issuer, &payload.signing_bytes(), signature)
+    }
+
+    /// Assembles a token from a payload and its signature by the payload's
+    /// issuer, verifying the signature.
+    ///
+    /// For payloads signed outside of this crate, see
+    /// [`RcanBuilder::to_signing_payload`].
+    pub fn from_signed_parts(payload: Payload<C, S>, signature: S::Signature) -> Result<Self>
+    where
+        C: Serialize,
+    {
+        let rcan = Rcan { signature, payload };
+        rcan.verify_signature()?;
+        Ok(rcan)
+    }
+
+    /// Verify the signature over the payload and associated data. The
+    /// signed bytes are `DST ++ postcard(payload) ++ postcard(aad)`,
+    /// matching [`RcanBuilder::sign_with_aad`].
+    fn verify_signature_with_aad(&self, aad: &[u8]) -> Result<()>
+    where
+        C: Serialize,
+    {
+        let signed = signing_preimage(&self.payload, Some(aad));
+        S::verify(&self.payload.issuer, &signed, &self.signature)
+    }
+
+    /// Verifies the token's signature against its embedded issuer.
+    ///
+    /// Tokens from [`Rcan::decode`] are already verified; this is for
+    /// tokens assembled or modified in memory.
+    pub fn verify_self(&self) -> Result<()>
+    where
+        C: Serialize,
+    {
+        self.verify_signature()
+    }
+
+    /// Verifies the token's signature and that it was issued by
+    /// `expected_issuer`, for flows where the issuer is known out of band.
+    pub fn verify_with_key(&self, expected_issuer: &S::VerifyingKey) -> Result<()>
+    where
+        C: Serialize,
+    {
+        if &self.payload.issuer != expected_issuer {
+            return Err(RcanError::IssuerMismatch {
+                expected: S::key_to_bytes(expected_issuer),
+                found: S::key_to_bytes(&self.payload.issuer),
+            });
+        }
+        self.verify_signature()
+    }
+
+    /// Verifies the token's signature and that `invoker` holds it, i.e. is
+    /// its audience or a member of its group audience.
+    ///
+    /// A cheap re-authentication for chains that were validated before.
+    /// Doesn't check expiry, or anything about the rest of the chain.
+    pub fn confirm_holder(&self, invoker: &S::VerifyingKey) -> Result<()>
+    where
+        C: Serialize,
+    {
+        if !self.audience_admits(invoker) {
+            return Err(RcanError::NotHolder(S::key_to_bytes(invoker)));
+        }
+        self.verify_signature()
+    }
+
+    /// Verifies the token's signature and that it is valid at `now`, and
+    /// returns its holder and its first capability.
+    ///
+    /// For handlers of single tokens without delegations. Doesn't check
+    /// who issued the token.
+    pub fn verify_and_split(&self, now: impl Into<Position>) -> Result<(&S::VerifyingKey, &C)>
+    where
+        C: Serialize,
+    {
+        let now = now.into();
+        self.verify_signature()?;
+        if !self.expires().is_valid_at(now) {
+            return Err(RcanError::Expired {
+                index: 0,
+                valid_until: self.expires().clone(),
+            });
+        }
+        if !self.valid_from().is_valid_at(now) {
+            return Err(RcanError::NotYetValid {
+                index: 0,
+                valid_from: self.valid_from().clone(),
+            });
+        }
+        Ok((self.audience(), self.capability()))
+    }
+
+    /// Verifies the token's signature and that `directory` trusts its
+    /// issuer, e.g. a cache of known keys.
+    ///
+    /// Any self-consistent token passes [`Rcan::verify_self`], since the
+    /// signature is checked against the key it names itself. This closes
+    /// that gap for verifiers that only accept known issuers.
+    pub fn verify_resolved(&self, directory: &impl Fn(&S::VerifyingKey) -> bool) -> Result<()>
+    where
+        C: Serialize,
+    {
+        if !directory(&self.payload.issuer) {
+            return Err(RcanError::UntrustedIssuer(S::key_to_bytes(
+                &self.payload.issuer,
+            )));
+        }
+        self.verify_signature()
+    }
+
+    /// Compares the encoded bytes of two tokens in constant time.
+    ///
+    /// Use this instead of `==` when checking incoming tokens against an
+    /// allowlist of exact tokens, so the comparison doesn't leak how many
+    /// leading bytes matched. Tokens of different encoded length compare
+    /// unequal, and the length itself is not hidden.
+    #[cfg(feature = "subtle")]
+    pub fn ct_eq(&self, other: &Self) -> subtle::Choice
+    where
+        C: Serialize,
+    {
+        use subtle::ConstantTimeEq;
+        self.encode().ct_eq(&other.encode())
+    }
+
+    /// The first of the [audiences](Rcan::audiences), the only one
+    /// unless the token was addressed to several.
+    pub fn audience(&self) -> &S::VerifyingKey {
+        &self.payload.audiences[0]
+    }
+
+    pub fn issuer(&self) -> &S::VerifyingKey {
+        &self.payload.issuer
+    }
+
+    /// Every audience the token is addressed to, [`Rcan::audience`] first,
+    /// never empty.
+    ///
+    /// Each of them is an audience in its own right: it may invoke the
+    /// capability, and issue the next delegation if the token isn't the
+    /// last in a chain. That delegation is then only addressed to its own
+    /// audiences, the other audiences of this token don't carry over.
+    ///
+    /// The [`Rcan::audience_members`] of a group audience are different:
+    /// they may invoke the capability on behalf of the group, the first
+    /// audience, but are no audiences themselves, so they can't issue
+    /// delegations of it.
+    pub fn audiences(&self) -> &[S::VerifyingKey] {
+        &self.payload.audiences
+    }
+
+    /// Whether this token is addressed to `key`, as one of its
+    /// [audiences](Rcan::audiences).
+    pub fn audience_is(&self, key: &S::VerifyingKey) -> bool {
+        self.audiences().contains(key)
+    }
+
+    /// The members of a group audience, empty unless the token was
+    /// addressed to a [`GroupKey`]. See [`Rcan::audiences`] for what they
+    /// may do.
+    pub fn audience_members(&self) -> &[S::VerifyingKey] {
+        &self.payload.audience_members
+    }
+
+    /// Whether `key` may invoke this token: one of its audiences or, for a
+    /// group audience, one of the members.
+    pub fn audience_admits(&self, key: &S::VerifyingKey) -> bool {
+        self.audience_is(key) || self.payload.audience_members.contains(key)
+    }
+
+    /// Whether this token was issued by `key`.
+    pub fn issuer_is(&self, key: &S::VerifyingKey) -> bool {
+        self.issuer() == key
+    }
+
+    /// The first capability, see [`Rcan::capabilities`].
+    pub fn capability(&self) -> &C {
+        self.payload.capability()
+    }
+
+    /// All capabilities the token grants, never empty. It grants
+    /// everything any one of them permits.
+    pub fn capabilities(&self) -> &[C] {
+        self.payload.capabilities()
+    }
+
+    /// Whether any of the token's capabilities permits `capability`.
+    pub fn permits(&self, capability: &C) -> bool
+    where
+        C: Capability,
+    {
+        self.capabilities()
+            .iter()
+            .any(|granted| granted.permits(capability))
+    }
+
+    pub fn capability_origin(&self) -> &CapabilityOrigin<S> {
+        self.payload.capability_origin()
+    }
+
+    /// Whether this token directly grants `requested` to `invoker` at
+    /// `now`: it is addressed to `invoker`, permits `requested` and is
+    /// valid at `now`.
+    ///
+    /// For single-token grants without delegations. Doesn't verify the
+    /// signature or who issued the token.
+    pub fn matches_request(
+        &self,
+        invoker: &S::VerifyingKey,
+        requested: &C,
+        now: impl Into<Position>,
+    ) -> bool
+    where
+        C: Capability,
+    {
+        let now = now.into();
+        self.audience_admits(invoker)
+            && self.permits(requested)
+            && self.expires().is_valid_at(now)
+            && self.valid_from().is_valid_at(now)
+    }
+
+    /// Consumes the token, returning its first capability.
+    pub fn into_capability(self) -> C {
+        self.payload
+            .capabilities
+            .into_iter()
+            .next()
+            .expect("at least one capability")
+    }
+
+    /// Consumes the token, returning its payload.
+    pub fn into_payload(self) -> Payload<C, S> {
+        self.payload
+    }
+
+    pub fn capability_issuer(&self) -> &S::VerifyingKey {
+        match self.payload.capability_origin() {
+            CapabilityOrigin::Issuer => &self.payload.issuer,
+            CapabilityOrigin::Delegation(ref root) => root,
+        }
+    }
+
+    pub fn expires(&self) -> &Expires {
+        &self.payload.valid_until
+    }
+
+    /// When the token becomes valid.
+    pub fn valid_from(&self) -> &NotBefore {
+        &self.payload.valid_from
+    }
+
+    /// When the token was issued, in unix seconds.
+    pub fn issued_at(&self) -> u64 {
+        self.payload.issued_at
+    }
+
+    /// The token's signed metadata, see [`Meta`].
+    pub fn meta(&self) -> &Meta {
+        self.payload.meta()
+    }
+
+    /// How many more times the audience may re-delegate this capability,
+    /// or `None` if this token doesn't limit it.
+    ///
+    /// [`Rcan::delegating_builder_from`] passes one less than this on to
+    /// the next link, to keep the limit visible further down the chain.
+    /// The root's limit is enforced either way when the chain is checked.
+    pub fn remaining_delegations_possible(&self) -> Option<u8> {
+        self.payload.max_delegation_depth
+    }
+
+    /// The only audiences a delegation following this token may be
+    /// addressed to, or `None` if this token doesn't restrict them.
+    pub fn delegation_audience_allowlist(&self) -> Option<&[S::VerifyingKey]> {
+        self.payload.delegation_audience_allowlist.as_deref()
+    }
+
+    /// Whether this token's allow-list permits delegating to `audience`.
+    fn allows_delegation_to(&self, audience: &S::VerifyingKey) -> bool {
+        self.delegation_audience_allowlist()
+            .is_none_or(|allowed| allowed.contains(audience))
+    }
+
+    /// Whether `next` can directly follow this token in a proof chain.
+    ///
+    /// This is a local check for assembling chains incrementally: `next`
+    /// must be issued by this token's audience, delegate the same root's
+    /// capability, have each of its capabilities permitted by one of this
+    /// token's, not exceed its delegation depth and be addressed to an
+    /// audience on its delegation allow-list, if any. It doesn't check
+    /// signatures or expiry.
+    pub fn links_to(&self, next: &Rcan<C, S>) -> bool
+    where
+        C: Capability,
+    {
+        self.audience_is(next.issuer())
+            && self.capability_issuer() == next.capability_issuer()
+            && next
+                .capabilities()
+                .iter()
+                .all(|capability| self.permits(capability))
+            && self.remaining_delegations_possible() != Some(0)
+            && self.capabilities().iter().all(C::may_delegate)
+            && next
+                .audiences()
+                .iter()
+                .all(|audience| self.allows_delegation_to(audience))
+    }
+}
+
+impl<C> Rcan<C> {
+    /// Like [`Rcan::from_signed_parts`], for a signature as the raw 64
+    /// bytes produced by a hardware token or other external signer.
+    pub fn from_raw(payload: Payload<C>, signature_bytes: [u8; SIGNATURE_LENGTH]) -> Result<Self>
+    where
+        C: Serialize,
+    {
+        Self::from_signed_parts(payload, Signature::from_bytes(&signature_bytes))
+    }
+
+    /// The token's [`Display`](core::fmt::Display) form, for logging.
+    pub fn summary(&self) -> String
+    where
+        C: core::fmt::Display,
+    {
+        format!("{self}")
+    }
+
+    /// Summarizes the token for display, e.g. by a command line tool
+    /// inspecting tokens. Validity is checked like in
+    /// [`Rcan::verify_and_split`], at `now`.
+    pub fn summarize(&self, now: impl Into<Position>) -> RcanSummary
+    where
+        C: core::fmt::Debug + Serialize,
+    {
+        RcanSummary {
+            version: VERSION,
+            issuer_hex: hex::encode(self.issuer()),
+            audience_hex: hex::encode(self.audience()),
+            origin: self.capability_origin().clone(),
+            capability_debug: format!("{:?}", self.capabilities()),
+            expires: self.expires().clone(),
+            valid: self.verify_and_split(now).is_ok(),
+            id: self.id(),
+        }
+    }
+
+    /// Verifies the signatures of many independent tokens at once, and
+    /// fails with the index of the first bad token.
+    ///
+    /// Decides like [`Rcan::verify_batch_tokens`], for callers that reject
+    /// the whole batch if any token is bad.
+    pub fn verify_batch(tokens: &[Rcan<C>]) -> Result<()>
+    where
+        C: Serialize,
+    {
+        let results = Self::verify_batch_tokens(tokens);
+        match results
+            .into_iter()
+            .enumerate()
+            .find_map(|(index, result)| Some(index).zip(result.err()))
+        {
+            None => Ok(()),
+            Some((index, RcanError::InvalidSignature(source))) => {
+                Err(RcanError::InvalidBatchSignature { index, source })
+            }
+            Some((_, err)) => Err(err),
+        }
+    }
+
+    /// Verifies the signatures of many independent tokens at once, with a
+    /// result per token, in order.
+    ///
+    /// The signatures are checked together with ed25519 batch
+    /// verification, over the same preimages [`Rcan::decode`] verifies,
+    /// and accepted or rejected exactly as [`Rcan::decode`] would. Tokens
+    /// whose key or `R` has a small-order component, or isn't canonically
+    /// encoded, are verified one by one instead, as are all tokens if the
+    /// batch fails, to find out which ones are bad.
+    ///
+    /// Tokens from [`Rcan::decode`] are already verified; this is for
+    /// tokens assembled from untrusted parts.
+    pub fn verify_batch_tokens(tokens: &[Rcan<C>]) -> Vec<Result<()>>
+    where
+        C: Serialize,
+    {
+        let preimages: Vec<Preimage> = tokens
+            .iter()
+            .map(|token| signing_preimage(&token.payload, None))
+            .collect();
+
+        // Batch verification doesn't reject small-order or non-canonical
+        // points like `verify_strict` does, and torsion can cancel out in
+        // its random combination. For canonical points of prime order,
+        // both agree.
+        let mut prime_keys = BTreeMap::new();
+        let batched: Vec<usize> = (0..tokens.len())
+            .filter(|&index| {
+                let Rcan { payload, signature } = &tokens[index];
+                *prime_keys
+                    .entry(payload.issuer.to_bytes())
+                    .or_insert_with(|| is_prime_order_point(payload.issuer.as_bytes()))
+                    && is_prime_order_point(signature.r_bytes())
+            })
+            .collect();
+        let messages: Vec<&[u8]> = batched.iter().map(|&i| preimages[i].as_slice()).collect();
+        let signatures: Vec<Signature> = batched.iter().map(|&i| tokens[i].signature).collect();
+        let keys: Vec<VerifyingKey> = batched.iter().map(|&i| tokens[i].payload.issuer).collect();
+        let batch_ok = ed25519_dalek::verify_batch(&messages, &signatures, &keys).is_ok();
+
+        let mut batched = batched.into_iter().peekable();
+        tokens
+            .iter()
+            .zip(&preimages)
+            .enumerate()
+            .map(|(index, (token, preimage))| {
+                if batched.next_if_eq(&index).is_some() && batch_ok {
+                    return Ok(());
+                }
+                Ed25519::verify(&token.payload.issuer, preimage, &token.signature)
+            })
+            .collect()
+    }
+}
+
+impl<'s, C, I: Issuer<S> + ?Sized, S: SignatureScheme> RcanBuilder<'s, C, I, S> {
+    /// Limits how many times the capability may be re-delegated after the
+    /// token being built. `0` forbids any further delegation.
+    pub fn max_delegation_depth(mut self, depth: u8) -> Self {
+        self.max_delegation_depth = Some(depth);
+        self
+    }
+
+    /// Restricts the audiences the capability may be re-delegated to: a
+    /// delegation following this token must be addressed to one of
+    /// `audiences`. An empty list forbids re-delegating, like a
+    /// [`RcanBuilder::max_delegation_depth`] of 0.
+    pub fn delegation_audience_allowlist(
+        mut self,
+        audiences: impl IntoIterator<Item = S::VerifyingKey>,
+    ) -> Self {
+        self.delegation_audience_allowlist = Some(audiences.into_iter().collect());
+        self
+    }
+
+    /// Makes the token valid only from `valid_from` on, e.g. for work
+    /// scheduled ahead of time. Tokens are valid immediately by default.
+    pub fn valid_from(mut self, valid_from: NotBefore) -> Self {
+        self.valid_from = valid_from;
+        self
+    }
+
+    /// Grants `capability` in addition to the ones already added. The token
+    /// then permits whatever any one of its capabilities permits.
+    pub fn add_capability(mut self, capability: C) -> Self {
+        self.capabilities.push(capability);
+        self
+    }
+
+    /// Records `time` as the token's issuance time, instead of the time
+    /// it is signed at.
+    #[cfg(feature = "std")]
+    pub fn issued_at(mut self, time: SystemTime) -> Self {
+        self.issued_at = Some(unix_secs(time));
+        self
+    }
+
+    /// Adds a metadata entry to the token, replacing any previous value
+    /// for `key`.
+    pub fn with_meta(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.meta.insert(key.into(), value.into());
+        self
+    }
+
+    /// Addresses the token to `group` instead of the audience passed to
+    /// the builder.
+    ///
+    /// The group's key becomes the audience, and any of its members may
+    /// invoke the capability directly. Further delegations still have to
+    /// be issued by the group's key.
+    pub fn audience_group(mut self, group: GroupKey<S>) -> Self {
+        self.audience = group.key;
+        self.audience_members = group.members;
+        self
+    }
+
+    /// Also addresses the token to `audiences`, besides the audience passed
+    /// to the builder, e.g. to several replicas of a service with keys of
+    /// their own.
+    ///
+    /// Unlike the members of an [`RcanBuilder::audience_group`], each of
+    /// them may also issue the next delegation, see [`Rcan::audiences`].
+    /// Duplicates are dropped.
+    pub fn add_audiences(mut self, audiences: impl IntoIterator<Item = S::VerifyingKey>) -> Self {
+        self.additional_audiences.extend(audiences);
+        self
+    }
+
+    /// The token's payload and the bytes to sign for it, for signing
+    /// outside of this crate, such as on an air-gapped machine.
+    ///
+    /// The bytes are the preimage [`RcanBuilder::sign`] signs. Assemble the
+    /// token from the payload and signature with [`Rcan::from_signed_parts`].
+    pub fn to_signing_payload(self, valid_until: Expires) -> (Payload<C, S>, Vec<u8>)
+    where
+        C: Serialize,
+    {
+        let (_, payload) = self.into_payload(valid_until);
+        let to_sign = payload.signing_bytes();
+        (payload, to_sign)
+    }
+
+    /// This builder with its capabilities replaced by `capabilities`.
+    fn with_capabilities<D>(&self, capabilities: Vec<D>) -> RcanBuilder<'s, D, I, S> {
+        RcanBuilder {
+            issuer: self.issuer,
+            audience: self.audience,
+            capability_origin: self.capability_origin.clone(),
+            capabilities,
+            max_delegation_depth: self.max_delegation_depth,
+            meta: self.meta.clone(),
+            audience_members: self.audience_members.clone(),
+            delegation_audience_allowlist: self.delegation_audience_allowlist.clone(),
+            additional_audiences: self.additional_audiences.clone(),
+            issued_at: self.issued_at,
+            valid_from: self.valid_from.clone(),
+        }
+    }
+
+    fn into_payload(self, valid_until: Expires) -> (&'s I, Payload<C, S>) {
+        let issuer = Issuer::verifying_key(self.issuer);
+        // Delegating your own capability is just issuing it. Collapse the
+        // redundant form so each meaning has a single encoding.
+        let capability_origin = match self.capability_origin {
+            CapabilityOrigin::Delegation(root) if root == issuer => CapabilityOrigin::Issuer,
+            origin => origin,
+        };
+        let mut audiences = vec![self.audience];
+        for audience in self.additional_audiences {
+            if !audiences.contains(&audience) {
+                audiences.push(audience);
+            }
+        }
+        let payload = Payload {
+            issuer,
+            audiences,
+            capability_origin,
+            capabilities: self.capabilities,
+            valid_until,
+            valid_from: self.valid_from,
+            issued_at: self.issued_at.unwrap_or_else(now_secs),
+            max_delegation_depth: self.max_delegation_depth,
+            meta: self.meta,
+            audience_members: self.audience_members,
+            delegation_audience_allowlist: self.delegation_audience_allowlist,
+        };
+        (self.issuer, payload)
+    }
+}
+
+impl<'s, C, I: AsyncSigner + ?Sized> RcanBuilder<'s, C, I> {
+    /// Signs the token with an asynchronous signer, such as a key in an
+    /// HSM.
+    ///
+    /// The token is the same [`RcanBuilder::sign`] would produce for an
+    /// in-memory key. The signature is verified before the token is
+    /// returned, so a misbehaving signer can't produce a broken token.
+    pub async fn sign_with(self, valid_until: Expires) -> Result<Rcan<C>>
+    where
+        C: Serialize,
+    {
+        let (signer, payload) = self.into_payload(valid_until);
+        let to_sign = signing_preimage(&payload, None);
+        let signature = signer.sign(&to_sign).await.map_err(RcanError::Signing)?;
+        Ed25519::verify(&payload.issuer, &to_sign, &signature)?;
+
+        Ok(Rcan { signature, payload })
+    }
+}
+
+impl<'s, C, S: SignatureScheme> RcanBuilder<'s, C, S::SigningKey, S> {
+    pub fn sign(self, valid_until: Expires) -> Rcan<C, S>
+    where
+        C: Serialize,
+    {
+        let (issuer, payload) = self.into_payload(valid_until);
+        Rcan::sign_payload(issuer, payload)
+    }
+
+    /// Signs a delegation following `parent`, after checking that it can
+    /// follow it in a proof chain.
+    ///
+    /// Fails without signing if `parent` isn't addressed to the issuer,
+    /// with [`RcanError::IssuerMismatch`], or if the delegation expires
+    /// after `parent`, on a different clock than it, or not at all while
+    /// `parent` does, with [`RcanError::ExpiresAfterParent`]. Any other
+    /// rule of [`Rcan::links_to`] that is broken fails with
+    /// [`RcanError::DelegationNotPermitted`].
+    pub fn sign_attenuated(self, parent: &Rcan<C, S>, valid_until: Expires) -> Result<Rcan<C, S>>
+    where
+        C: Capability,
+    {
+        let issuer = S::verifying_key(self.issuer);
+        if !parent.audience_is(&issuer) {
+            return Err(RcanError::IssuerMismatch {
+                expected: S::key_to_bytes(parent.audience()),
+                found: S::key_to_bytes(&issuer),
+            });
+        }
+        let owner = match &self.capability_origin {
+            CapabilityOrigin::Issuer => &issuer,
+            CapabilityOrigin::Delegation(owner) => owner,
+        };
+        if owner != parent.capability_issuer() {
+            return Err(RcanError::DelegationNotPermitted(
+                "it delegates a different owner's capability",
+            ));
+        }
+        if !self
+            .capabilities
+            .iter()
+            .all(|capability| parent.permits(capability))
+        {
+            return Err(RcanError::DelegationNotPermitted(
+                "the parent doesn't permit all of its capabilities",
+            ));
+        }
+        if parent.remaining_delegations_possible() == Some(0) {
+            return Err(RcanError::DelegationNotPermitted(
+                "the parent allows no further delegations",
+            ));
+        }
+        if !parent.capabilities().iter().all(C::may_delegate) {
+            return Err(RcanError::DelegationNotPermitted(
+                "the parent's capabilities don't allow re-delegation",
+            ));
+        }
+        if !core::iter::once(&self.audience)
+            .chain(&self.additional_audiences)
+            .all(|audience| parent.allows_delegation_to(audience))
+        {
+            return Err(RcanError::DelegationNotPermitted(
+                "an audience isn't on the parent's allow-list",
+            ));
+        }
+        if !valid_until.is_within(parent.expires()) {
+            return Err(RcanError::ExpiresAfterParent {
+                valid_until,
+                parent: parent.expires().clone(),
+            });
+        }
+
+        let rcan = self.sign(valid_until);
+        debug_assert!(parent.links_to(&rcan));
+        Ok(rcan)
+    }
+
+    /// Signs one token per parameter, for builders whose capabilities are
+    /// [`CapabilityTemplate`]s. Each token carries the templates
+    /// instantiated with its parameter, and is otherwise the same.
+    pub fn issue_from_template<'p, D>(
+        self,
+        params: impl IntoIterator<Item = &'p C::Param>,
+        valid_until: Expires,
+    ) -> Vec<Rcan<D, S>>
+    where
+        C: CapabilityTemplate<D>,
+        C::Param: 'p,
+        D: Serialize,
+    {
+        params
+            .into_iter()
+            .map(|param| {
+                let capabilities = self
+                    .capabilities
+                    .iter()
+                    .map(|template| template.instantiate(param))
+                    .collect();
+                self.with_capabilities(capabilities)
+                    .sign(valid_until.clone())
+            })
+            .collect()
+    }
+
+    /// Signs the token bound to external context, like a service name or
+    /// environment, without putting that context into the payload.
+    ///
+    /// Similar to AEAD associated data, `aad` is mixed into the signed
+    /// bytes but not transmitted: the token only verifies with
+    /// [`Rcan::decode_with_aad`] given the same `aad`, and never with
+    /// plain [`Rcan::decode`].
+    pub fn sign_with_aad(self, valid_until: Expires, aad: &[u8]) -> Rcan<C, S>
+    where
+        C: Serialize,
+    {
+        let (issuer, payload) = self.into_payload(valid_until);
+
+        let to_sign = signing_preimage(&payload, Some(aad));
+        let signature = S::sign(issuer, &to_sign);
+
+        Rcan { signature, payload }
+    }
+}
+
+/// The bytes signed for a token, zeroized on drop with the `zeroize`
+/// feature.
+#[cfg(feature = "zeroize")]
+type Preimage = zeroize::Zeroizing<Vec<u8>>;
+#[cfg(not(feature = "zeroize"))]
+type Preimage = Vec<u8>;
+
+/// The signed bytes of a token, `DST ++ postcard(payload)`, followed by
+/// the associated data if there is any. `aad` is length prefixed, so a
+/// token's preimage never collides with one without `aad`.
+///
+/// The buffer is allocated at its final size and never grows, so with the
+/// `zeroize` feature no unzeroized copy of it is left behind.
+// Without `zeroize` the buffer already is a `Preimage`.
+#[cfg_attr(not(feature = "zeroize"), allow(clippy::useless_conversion))]
+fn signing_preimage<C: Serialize, S: SignatureScheme>(
+    payload: &Payload<C, S>,
+    aad: Option<&[u8]>,
+) -> Preimage {
+    let len = preimage_len(payload, aad);
+    let buffer = write_preimage(Vec::with_capacity(len), payload, aad);
+    debug_assert!(buffer.len() == len && buffer.capacity() >= len);
+    buffer.into()
+}
+
+/// The length of the [`signing_preimage`] of `payload` and `aad`.
+fn preimage_len<C: Serialize, S: SignatureScheme>(
+    payload: &Payload<C, S>,
+    aad: Option<&[u8]>,
+) -> usize {
+    use postcard::experimental::serialized_size;
+
+    DST.len()
+        + serialized_size(payload).expect("vec")
+        + aad.map_or(0, |aad| serialized_size(aad).expect("vec"))
+}
+
+/// Appends the [`signing_preimage`] of `payload` and `aad` to `buffer`.
+fn write_preimage<C: Serialize, S: SignatureScheme>(
+    mut buffer: Vec<u8>,
+    payload: &Payload<C, S>,
+    aad: Option<&[u8]>,
+) -> Vec<u8> {
+    buffer.extend_from_slice(DST);
+    let mut buffer = postcard::to_extend(payload, buffer).expect("vec");
+    if let Some(aad) = aad {
+        buffer = postcard::to_extend(aad, buffer).expect("vec");
+    }
+    buffer
+}
+
+/// Whether `bytes` canonically encode a curve point of prime order, for
+/// which batch verification agrees with `verify_strict`.
+fn is_prime_order_point(bytes: &[u8; 32]) -> bool {
+    // `VerifyingKey` decompresses any point, not just keys.
+    VerifyingKey::from_bytes(bytes).is_ok_and(|point| {
+        let point = point.to_edwards();
+        !point.is_small_order() && point.is_torsion_free() && point.compress().as_bytes() == bytes
+    })
+}
+
+/// Verifies that every proof in the chain permits `capability`, failing
+/// with the index of the first that doesn't.
+fn check_permitted<C: Capability, S: SignatureScheme>(
+    proof_chain: &[&Rcan<C, S>],
+    capability: &C,
+) -> Result<()> {
+    match proof_chain
+        .iter()
+        .position(|proof| !proof.permits(capability))
+    {
+        Some(i) => Err(RcanError::CapabilityNotPermitted { proof: i }),
+        None => Ok(()),
+    }
+}
+
+/// The current time in unix seconds, when a token is signed. Without
+/// `std` there is no clock, and tokens are issued at the epoch.
+fn now_secs() -> u64 {
+    #[cfg(feature = "std")]
+    {
+        unix_secs(SystemTime::now())
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        0
+    }
+}
+
+/// Whole seconds since the unix epoch.
+#[cfg(feature = "std")]
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .expect("time must be after UNIX_EPOCH")
+        .as_secs()
+}
+
+/// Orders expiries chronologically, with [`Expires::Never`] after every
+/// other expiry.
+///
+/// Wall-clock expiries and block heights aren't comparable, block heights
+/// are ordered after any [`Expires::At`]. A chain mixing both is never
+/// valid at any [`Position`], so that order never decides a chain's
+/// effective expiry.
+impl Ord for Expires {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        match (self, other) {
+            (Expires::Never, Expires::Never) => core::cmp::Ordering::Equal,
+            (Expires::Never, _) => core::cmp::Ordering::Greater,
+            (_, Expires::Never) => core::cmp::Ordering::Less,
+            (Expires::At(a), Expires::At(b)) => a.cmp(b),
+            (Expires::AtBlock(a), Expires::AtBlock(b)) => a.cmp(b),
+            (Expires::At(_), Expires::AtBlock(_)) => core::cmp::Ordering::Less,
+            (Expires::AtBlock(_), Expires::At(_)) => core::cmp::Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for Expires {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Expires {
+    #[cfg(feature = "std")]
+    pub fn valid_for(duration: Duration) -> Self {
+        Self::At(
+            (SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("now is after UNIX_EPOCH")
+                + duration)
+                .as_secs(),
+        )
+    }
+
+    /// The earliest of the given expiries, e.g. the effective expiry of a
+    /// chain. [`Expires::Never`] if there are none.
+    pub fn earliest(expiries: impl IntoIterator<Item = Expires>) -> Self {
+        expiries.into_iter().min().unwrap_or(Expires::Never)
+    }
+
+    /// The latest of the given expiries. [`Expires::Never`] if there are
+    /// none, or if any of them never expires.
+    pub fn latest(expiries: impl IntoIterator<Item = Expires>) -> Self {
+        expiries.into_iter().max().unwrap_or(Expires::Never)
+    }
+
+    /// Shorthand for [`Expires::valid_for`] with a duration in whole seconds.
+    #[cfg(feature = "std")]
+    pub fn valid_for_secs(secs: u64) -> Self {
+        Self::valid_for(Duration::from_secs(secs))
+    }
+
+    /// Whether this hasn't expired at `at`, e.g. a `SystemTime`, see
+    /// [`Position`].
+    pub fn is_valid_at(&self, at: impl Into<Position>) -> bool {
+        match (self, at.into()) {
+            (Expires::Never, _) => true,
+            (Expires::At(expiry), Position::Time(time)) => *expiry >= time,
+            (Expires::AtBlock(expiry), Position::Block(height)) => *expiry >= height,
+            (Expires::At(_), Position::Block(_)) | (Expires::AtBlock(_), Position::Time(_)) => {
+                false
+            }
+        }
+    }
+
+    /// Whether this expires no later than `parent`, on the same clock,
+    /// or `parent` never expires.
+    fn is_within(&self, parent: &Expires) -> bool {
+        match (self, parent) {
+            (_, Expires::Never) => true,
+            (Expires::At(expiry), Expires::At(parent)) => expiry <= parent,
+            (Expires::AtBlock(expiry), Expires::AtBlock(parent)) => expiry <= parent,
+            _ => false,
+        }
+    }
+
+    /// Whether this expires at or before `now + within`, e.g. to renew
+    /// tokens ahead of time. Block heights don't expire on the wall clock,
+    /// so this is always `false` for them.
+    #[cfg(feature = "std")]
+    pub fn expires_within(&self, within: Duration, now: SystemTime) -> bool {
+        match self {
+            Expires::Never | Expires::AtBlock(_) => false,
+            Expires::At(expiry) => *expiry <= unix_secs(now + within),
+        }
+    }
+}
+
+impl NotBefore {
+    /// Whether this is valid at `at`, e.g. a `SystemTime`, see
+    /// [`Position`]. Timestamps are never valid at a block height.
+    pub fn is_valid_at(&self, at: impl Into<Position>) -> bool {
+        match (self, at.into()) {
+            (NotBefore::Immediately, _) => true,
+            (NotBefore::At(from), Position::Time(time)) => *from <= time,
+            (NotBefore::At(_), Position::Block(_)) => false,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use testresult::TestResult;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, derive_more::Display)]
+    pub(crate) enum Rpc {
+        Read,
+        ReadWrite,
+        /// Read, ReadWrite, and any "future ones" that we might not have thought of yet.
+        All,
+    }
+
+    impl Capability for Rpc {
+        fn permits(&self, other: &Self) -> bool {
+            match (self, other) {
+                // `All` permits all RPC operations, by definition
+                (Rpc::All, _) => true,
+                // `ReadWrite` permits `Read` and `ReadWrite`, but not `All` (which may be extended later to include more caps)
+                (Rpc::ReadWrite, Rpc::ReadWrite | Rpc::Read) => true,
+                (Rpc::ReadWrite, _) => false,
+                // `Read` only permits `Read`
+                (Rpc::Read, Rpc::Read) => true,
+                (Rpc::Read, _) => false,
+            }
+        }
+    }
+
+    #[test]
+    fn test_simple_capabilitys() {
+        assert!(Rpc::Read.permits(&Rpc::Read));
+        assert!(Rpc::ReadWrite.permits(&Rpc::Read));
+        assert!(Rpc::ReadWrite.permits(&Rpc::ReadWrite),);
+        assert!(!Rpc::Read.permits(&Rpc::ReadWrite));
+        assert!(!Rpc::Read.permits(&Rpc::All));
+        assert!(Rpc::All.permits(&Rpc::All));
+        assert!(Rpc::All.permits(&Rpc::Read));
+        assert!(Rpc::All.permits(&Rpc::ReadWrite));
+    }
+
+    #[test]
+    fn test_rcan_encoding() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]);
+        let rcan = Rcan::issuing_builder(&issuer, audience.verifying_key(), Rpc::ReadWrite)
+            .issued_at(SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+            .sign(Expires::Never);
+
+        println!("{}", hex::encode(rcan.encode()));
+        println!(
+            "{}",
+            hex::encode(postcard::to_allocvec(&rcan.signature).unwrap())
+        );
+
+        let expected: String = [
+            // Version
+            "02",
+            // Issuer
+            "203b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29",
+            // audiences: [Audience]
+            "01",
+            "208a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c",
+            // Capability Origin: Issuer
+            "00",
+            // capabilities: [Rpc::ReadWrite]
+            "01",
+            "01",
+            // Expires::Never
+            "00",
+            // NotBefore::Immediately
+            "00",
+            // issued_at: 1
+            "01",
+            // max_delegation_depth: None
+            "00",
+            // meta: empty
+            "00",
+            // audience_members: empty
+            "00",
+            // delegation_audience_allowlist: None
+            "00",
+            // Signature
+            "f8b14b383f6200c8070cadff4b1c1cb76906f9ea3a988cc3b1f7be7a6dcc729a6fc81c22eb98ef4ccf22c5360876f351e0c658662627f0f6b180a8d6f182b90d",
+        ]
+        .join("");
+
+        assert_eq!(hex::encode(rcan.encode()), expected);
+        assert_eq!(Rcan::decode(&rcan.encode())?, rcan);
+        Ok(())
+    }
+
+    #[cfg(feature = "subtle")]
+    #[test]
+    fn test_rcan_ct_eq() {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]);
+        let issued_at = SystemTime::now();
+        let read = Rcan::issuing_builder(&issuer, audience.verifying_key(), Rpc::Read)
+            .issued_at(issued_at)
+            .sign(Expires::Never);
+        let read_write = Rcan::issuing_builder(&issuer, audience.verifying_key(), Rpc::ReadWrite)
+            .issued_at(issued_at)
+            .sign(Expires::Never);
+
+        // ed25519 signatures are deterministic, so signing the same payload
+        // again yields an identical token.
+        let read_again = Rcan::issuing_builder(&issuer, audience.verifying_key(), Rpc::Read)
+            .issued_at(issued_at)
+            .sign(Expires::Never);
+        assert!(bool::from(read.ct_eq(&read_again)));
+        assert!(!bool::from(read.ct_eq(&read_write)));
+    }
+
+    #[test]
+    fn deserialize_rejects_forged_signature() {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]);
+        let rcan = Rcan::issuing_builder(&issuer, audience.verifying_key(), Rpc::ReadWrite)
+            .sign(Expires::Never);
+
+        // A genuine token round-trips through serde.
+        let mut wire = postcard::to_allocvec(&rcan).unwrap();
+        assert_eq!(postcard::from_bytes::<Rcan<Rpc>>(&wire).unwrap(), rcan);
+
+        // The trailing bytes are the signature. Zeroing them must make
+        // deserialization fail rather than yield an unverified token.
+        let n = wire.len();
+        wire[n - SIGNATURE_LENGTH..].fill(0);
+        assert!(postcard::from_bytes::<Rcan<Rpc>>(&wire).is_err());
+    }
+
+    #[test]
+    fn test_rcan_invocation() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+
+        // The service gives alice access to everything for 60 seconds
+        let service_rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .sign(Expires::valid_for(Duration::from_secs(60)));
+        // alice gives attenuated (only read access) to bob, but doesn't care for how long still
+        let friend_rcan = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+        // bob can now pass the authorization test for the service
+        let service_auth = Authorizer::new(service.verifying_key());
+        assert!(service_auth
+            .check_invocation_from(
+                bob.verifying_key(),
+                Rpc::Read,
+                &[&service_rcan, &friend_rcan],
+            )
+            .is_ok());
+
+        // but bob doesn't have read-write access
+        assert!(service_auth
+            .check_invocation_from(
+                bob.verifying_key(),
+                Rpc::ReadWrite,
+                &[&service_rcan, &friend_rcan]
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcan_invocation_all() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+
+        let service_rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .sign(Expires::valid_for(Duration::from_secs(60)));
+        let service_auth = Authorizer::new(service.verifying_key());
+
+        let _ = service_auth.check_invocation_from_all(
+            alice.verifying_key(),
+            &[Rpc::Read, Rpc::Read],
+            &[&service_rcan],
+        )?;
+
+        // A read-only chain can't authorize a batch that also writes,
+        // and the error points at the `ReadWrite` entry.
+        let requested = [Rpc::Read, Rpc::ReadWrite];
+        let err = service_auth
+            .check_invocation_from_all(alice.verifying_key(), &requested, &[&service_rcan])
+            .unwrap_err();
+        let RcanError::RequestNotPermitted { request, proof } = err else {
+            panic!("{err}");
+        };
+        assert_eq!(requested[request], Rpc::ReadWrite);
+        assert_eq!(proof, 0);
+
+        // Attenuated by a read-only delegation, the denying proof is the
+        // delegation rather than the root.
+        let service_rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .sign(Expires::valid_for(Duration::from_secs(60)));
+        let friend_rcan = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+        let err = service_auth
+            .check_invocation_from_all(
+                bob.verifying_key(),
+                &requested,
+                &[&service_rcan, &friend_rcan],
+            )
+            .unwrap_err();
+        let RcanError::RequestNotPermitted { request, proof } = err else {
+            panic!("{err}");
+        };
+        assert_eq!(requested[request], Rpc::ReadWrite);
+        assert_eq!(proof, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_delegation_depth() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+
+        // The root grant may be re-delegated once.
+        let service_rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .max_delegation_depth(1)
+            .sign(Expires::Never);
+        assert_eq!(service_rcan.remaining_delegations_possible(), Some(1));
+        let alice_rcan = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+        let bob_rcan = Rcan::delegating_builder(
+            &bob,
+            carol.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+
+        let service_auth = Authorizer::new(service.verifying_key());
+        let _ = service_auth.check_invocation_from(
+            bob.verifying_key(),
+            Rpc::Read,
+            &[&service_rcan, &alice_rcan],
+        )?;
+        assert!(service_auth
+            .check_invocation_from(
+                carol.verifying_key(),
+                Rpc::Read,
+                &[&service_rcan, &alice_rcan, &bob_rcan],
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delegating_builder_from() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+
+        // Each link counts down the root's limit.
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .max_delegation_depth(2)
+            .sign(Expires::Never);
+        let alice_rcan =
+            Rcan::delegating_builder_from(&alice, bob.verifying_key(), &root, Rpc::ReadWrite)
+                .sign(Expires::Never);
+        assert_eq!(alice_rcan.capability_issuer(), &service.verifying_key());
+        assert_eq!(alice_rcan.remaining_delegations_possible(), Some(1));
+        let bob_rcan =
+            Rcan::delegating_builder_from(&bob, carol.verifying_key(), &alice_rcan, Rpc::Read)
+                .sign(Expires::Never);
+        assert_eq!(bob_rcan.remaining_delegations_possible(), Some(0));
+        assert!(root.links_to(&alice_rcan) && alice_rcan.links_to(&bob_rcan));
+        let _ = Authorizer::new(service.verifying_key()).check_invocation_from(
+            carol.verifying_key(),
+            Rpc::Read,
+            &[&root, &alice_rcan, &bob_rcan],
+        )?;
+
+        // No further link, and no limit where the parent has none.
+        let carol_rcan =
+            Rcan::delegating_builder_from(&carol, alice.verifying_key(), &bob_rcan, Rpc::Read)
+                .sign(Expires::Never);
+        assert!(!bob_rcan.links_to(&carol_rcan));
+        let unlimited =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All).sign(Expires::Never);
+        let next =
+            Rcan::delegating_builder_from(&alice, bob.verifying_key(), &unlimited, Rpc::Read)
+                .sign(Expires::Never);
+        assert_eq!(next.remaining_delegations_possible(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reanchor() -> TestResult {
+        let old_service = SigningKey::from_bytes(&[0u8; 32]);
+        let new_service = SigningKey::from_bytes(&[9u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+
+        let service_rcan = Rcan::issuing_builder(&old_service, alice.verifying_key(), Rpc::All)
+            .sign(Expires::Never);
+        let friend_rcan = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            old_service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+        let old_chain = [&service_rcan, &friend_rcan];
+
+        // After rotation the old chain no longer verifies under the new key.
+        let new_auth = Authorizer::new(new_service.verifying_key());
+        assert!(new_auth
+            .check_invocation_from(bob.verifying_key(), Rpc::Read, &old_chain)
+            .is_err());
+
+        // The new root cross-signs the old root.
+        let cross_sign = Rcan::issuing_builder(&new_service, old_service.verifying_key(), Rpc::All)
+            .sign(Expires::Never);
+        let reanchored = ReanchoredChain::new(&cross_sign, &old_chain)?;
+        assert_eq!(reanchored.root(), &new_service.verifying_key());
+
+        let _ = new_auth.check_reanchored_invocation_from(
+            bob.verifying_key(),
+            Rpc::Read,
+            &reanchored,
+        )?;
+        assert!(new_auth
+            .check_reanchored_invocation_from(bob.verifying_key(), Rpc::ReadWrite, &reanchored)
+            .is_err());
+
+        // Some other authorizer can't use the cross-signing token.
+        let other_auth = Authorizer::new(alice.verifying_key());
+        assert!(other_auth
+            .check_reanchored_invocation_from(bob.verifying_key(), Rpc::Read, &reanchored)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_links_to() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+
+        let service_rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .sign(Expires::Never);
+        let friend_rcan = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+        assert!(service_rcan.links_to(&friend_rcan));
+
+        // escalates the capability
+        let escalating_rcan = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::All,
+        )
+        .sign(Expires::Never);
+        assert!(!service_rcan.links_to(&escalating_rcan));
+        // wrong direction
+        assert!(!friend_rcan.links_to(&service_rcan));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_rcan_invocation_async() -> TestResult {
+        /// Mock revocation service that revokes every token issued by a key.
+        struct RevokedIssuer(VerifyingKey);
+
+        impl AsyncRevocationSet<Rpc> for RevokedIssuer {
+            async fn is_revoked(&self, proof: &Rcan<Rpc>) -> Result<bool, BoxError> {
+                n0_future::future::yield_now().await;
+                Ok(proof.issuer() == &self.0)
+            }
+        }
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let mallory = SigningKey::from_bytes(&[3u8; 32]);
+
+        let service_rcan =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All).sign(Expires::Never);
+        let friend_rcan = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+        let chain = [&service_rcan, &friend_rcan];
+        let service_auth = Authorizer::new(service.verifying_key());
+
+        n0_future::future::block_on(async {
+            let _ = service_auth
+                .check_invocation_from_async(
+                    bob.verifying_key(),
+                    Rpc::Read,
+                    &chain,
+                    RevokedIssuer(mallory.verifying_key()),
+                )
+                .await?;
+
+            // alice's delegation to bob has been revoked
+            let err = service_auth
+                .check_invocation_from_async(
+                    bob.verifying_key(),
+                    Rpc::Read,
+                    &chain,
+                    RevokedIssuer(alice.verifying_key()),
+                )
+                .await
+                .unwrap_err();
+            assert!(matches!(err, RcanError::Revoked { index: 1 }), "{err}");
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_sign_with() -> TestResult {
+        /// Mock HSM holding a key, or failing if it holds none.
+        struct Hsm {
+            key: Option<SigningKey>,
+            public: VerifyingKey,
+        }
+
+        impl AsyncSigner for Hsm {
+            async fn sign(&self, msg: &[u8]) -> Result<Signature, BoxError> {
+                n0_future::future::yield_now().await;
+                let key = self.key.as_ref().ok_or("HSM unavailable")?;
+                Ok(Signer::sign(key, msg))
+            }
+
+            fn verifying_key(&self) -> VerifyingKey {
+                self.public
+            }
+        }
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let hsm = Hsm {
+            key: Some(service.clone()),
+            public: service.verifying_key(),
+        };
+        let issued_at = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+
+        n0_future::future::block_on(async {
+            let remote = Rcan::issuing_builder(&hsm, alice.verifying_key(), Rpc::ReadWrite)
+                .with_meta("ticket", "42")
+                .issued_at(issued_at)
+                .sign_with(Expires::At(1000))
+                .await?;
+            let local = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+                .with_meta("ticket", "42")
+                .issued_at(issued_at)
+                .sign(Expires::At(1000));
+            assert_eq!(remote.encode(), local.encode());
+            assert_eq!(Rcan::<Rpc>::decode(&remote.encode())?, local);
+
+            // In-memory keys sign asynchronously as well.
+            let from_key = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+                .with_meta("ticket", "42")
+                .issued_at(issued_at)
+                .sign_with(Expires::At(1000))
+                .await?;
+            assert_eq!(from_key, local);
+
+            let unavailable = Hsm {
+                key: None,
+                public: service.verifying_key(),
+            };
+            let err = Rcan::issuing_builder(&unavailable, alice.verifying_key(), Rpc::Read)
+                .sign_with(Expires::Never)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, RcanError::Signing(_)), "{err}");
+
+            // A signature by another key than the signer claims is rejected.
+            let confused = Hsm {
+                key: Some(bob.clone()),
+                public: service.verifying_key(),
+            };
+            let err = Rcan::issuing_builder(&confused, alice.verifying_key(), Rpc::Read)
+                .sign_with(Expires::Never)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, RcanError::InvalidSignature(_)), "{err}");
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_from_signed_parts() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let issued_at = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+
+        // Only the public key is needed to build the payload.
+        let (payload, to_sign) =
+            Rcan::issuing_builder(&service.verifying_key(), alice.verifying_key(), Rpc::Read)
+                .issued_at(issued_at)
+                .to_signing_payload(Expires::At(1000));
+        let signature = Signer::sign(&service, &to_sign);
+        let rcan = Rcan::from_signed_parts(payload.clone(), signature)?;
+        let local = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .issued_at(issued_at)
+            .sign(Expires::At(1000));
+        assert_eq!(rcan, local);
+
+        let forged = Signer::sign(&alice, &to_sign);
+        let err = Rcan::from_signed_parts(payload.clone(), forged).unwrap_err();
+        assert!(matches!(err, RcanError::InvalidSignature(_)), "{err}");
+
+        // Raw signature bytes, as handed out by a hardware token.
+        let raw = Signer::sign(&service, &to_sign).to_bytes();
+        assert_eq!(Rcan::from_raw(payload.clone(), raw)?, local);
+        let err = Rcan::from_raw(payload, [0; SIGNATURE_LENGTH]).unwrap_err();
+        assert!(matches!(err, RcanError::InvalidSignature(_)), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_payload_signature() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+
+        // The signer only hands out the payload and its signature.
+        let (payload, _) =
+            Rcan::issuing_builder(&service.verifying_key(), alice.verifying_key(), Rpc::Read)
+                .to_signing_payload(Expires::At(1000));
+        let signature = Signer::sign(&service, &payload.signing_bytes());
+        let received: Payload<Rpc> = postcard::from_bytes(&postcard::to_allocvec(&payload)?)?;
+
+        // The verifier rebuilds the signed bytes from the payload alone.
+        assert_eq!(received.issuer(), &service.verifying_key());
+        assert_eq!(received.signing_bytes(), payload.signing_bytes());
+        Rcan::verify_payload_signature(&received, &signature)?;
+
+        let forged = Signer::sign(&alice, &payload.signing_bytes());
+        let err = Rcan::verify_payload_signature(&received, &forged).unwrap_err();
+        assert!(matches!(err, RcanError::InvalidSignature(_)), "{err}");
+        let other = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .sign(Expires::At(1000));
+        let err = Rcan::verify_payload_signature(&other.payload, &signature).unwrap_err();
+        assert!(matches!(err, RcanError::InvalidSignature(_)), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_attenuated() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.verifying_key();
+        let parent = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .sign(Expires::At(1000));
+        let delegate = |issuer, capability| {
+            Rcan::delegating_builder(issuer, bob.verifying_key(), owner, capability)
+        };
+
+        let rcan = delegate(&alice, Rpc::Read).sign_attenuated(&parent, Expires::At(500))?;
+        assert!(parent.links_to(&rcan));
+
+        let err = delegate(&alice, Rpc::All)
+            .sign_attenuated(&parent, Expires::At(500))
+            .unwrap_err();
+        assert!(matches!(err, RcanError::DelegationNotPermitted(_)), "{err}");
+
+        for valid_until in [Expires::At(1001), Expires::AtBlock(10), Expires::Never] {
+            let err = delegate(&alice, Rpc::Read)
+                .sign_attenuated(&parent, valid_until)
+                .unwrap_err();
+            assert!(matches!(err, RcanError::ExpiresAfterParent { .. }), "{err}");
+        }
+
+        // Only the parent's audience can delegate it.
+        let err = delegate(&bob, Rpc::Read)
+            .sign_attenuated(&parent, Expires::At(500))
+            .unwrap_err();
+        assert!(matches!(err, RcanError::IssuerMismatch { .. }), "{err}");
+
+        // Other link rules are checked as well.
+        let final_parent = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .max_delegation_depth(0)
+            .sign(Expires::Never);
+        let err = delegate(&alice, Rpc::Read)
+            .sign_attenuated(&final_parent, Expires::Never)
+            .unwrap_err();
+        assert!(matches!(err, RcanError::DelegationNotPermitted(_)), "{err}");
+        let restricted = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .delegation_audience_allowlist([service.verifying_key()])
+            .sign(Expires::Never);
+        let err = delegate(&alice, Rpc::Read)
+            .sign_attenuated(&restricted, Expires::Never)
+            .unwrap_err();
+        assert!(matches!(err, RcanError::DelegationNotPermitted(_)), "{err}");
+        let err =
+            Rcan::delegating_builder(&alice, bob.verifying_key(), bob.verifying_key(), Rpc::Read)
+                .sign_attenuated(&parent, Expires::At(500))
+                .unwrap_err();
+        assert!(matches!(err, RcanError::DelegationNotPermitted(_)), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .sign(Expires::Never);
+        assert_eq!(
+            rcan.to_string(),
+            "rcan[ iss=ed25519:3b6a27bc… aud=ed25519:8a88e3dd… cap=ReadWrite origin=Issuer expires=never ]"
+        );
+        assert_eq!(rcan.summary(), rcan.to_string());
+
+        let delegation = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .add_capability(Rpc::ReadWrite)
+        .add_audiences([service.verifying_key()])
+        .sign(Expires::At(1000));
+        let summary = delegation.summary();
+        assert_eq!(
+            summary,
+            format!(
+                "rcan[ iss=ed25519:8a88e3dd… aud=ed25519:{}…,ed25519:3b6a27bc… \
+                 cap=Read,ReadWrite origin=Delegation(ed25519:3b6a27bc…) expires=1000 ]",
+                hex::encode(&bob.verifying_key().as_bytes()[..4])
+            )
+        );
+        // No signature bytes.
+        let signature = hex::encode(delegation.signature.to_bytes());
+        assert!(!summary.contains(&signature[..8]));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .add_capability(Rpc::Read)
+            .sign(Expires::At(1000));
+
+        let summary = rcan.summarize(Position::Time(500));
+        assert_eq!(
+            summary,
+            RcanSummary {
+                version: VERSION,
+                issuer_hex: "3b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29"
+                    .to_string(),
+                audience_hex: "8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c"
+                    .to_string(),
+                origin: CapabilityOrigin::Issuer,
+                capability_debug: "[ReadWrite, Read]".to_string(),
+                expires: Expires::At(1000),
+                valid: true,
+                id: rcan.id(),
+            }
+        );
+        assert!(!rcan.summarize(Position::Time(1001)).valid);
+
+        let mut forged = rcan.clone();
+        forged.payload.capabilities[0] = Rpc::All;
+        assert!(!forged.summarize(Position::Time(500)).valid);
+    }
+
+    #[test]
+    fn test_monotonic_expiry() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.verifying_key();
+        let authorizer = Authorizer::new(owner).with_monotonic_expiry();
+        let chain = |parent_expiry, child_expiry| {
+            let parent = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+                .sign(parent_expiry);
+            let child = Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Rpc::Read)
+                .sign(child_expiry);
+            (parent, child)
+        };
+        let check = |authorizer: &Authorizer, (parent, child): (Rcan<Rpc>, Rcan<Rpc>)| {
+            authorizer.check_invocation_from_at(
+                Position::Time(100),
+                bob.verifying_key(),
+                Rpc::Read,
+                &[&parent, &child],
+            )
+        };
+
+        // attenuating
+        let _ = check(&authorizer, chain(Expires::At(1000), Expires::At(500)))?;
+        let _ = check(&authorizer, chain(Expires::At(1000), Expires::At(1000)))?;
+        // a parent that never expires
+        let _ = check(&authorizer, chain(Expires::Never, Expires::At(500)))?;
+        let _ = check(&authorizer, chain(Expires::Never, Expires::Never))?;
+
+        // outliving the parent
+        for child_expiry in [Expires::At(1001), Expires::Never] {
+            let err =
+                check(&authorizer, chain(Expires::At(1000), child_expiry.clone())).unwrap_err();
+            assert!(
+                matches!(err, RcanError::OutlivesParent { index: 1 }),
+                "{err}"
+            );
+            // Only rejected when asked for.
+            let _ = check(
+                &Authorizer::new(owner),
+                chain(Expires::At(1000), child_expiry),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_invocation_from_any_invoker() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let devices: Vec<_> = (1..=3u8)
+            .map(|i| SigningKey::from_bytes(&[i; 32]).verifying_key())
+            .collect();
+        let authorizer = Authorizer::new(service.verifying_key());
+        let rcan = Rcan::issuing_builder(&service, devices[1], Rpc::ReadWrite).sign(Expires::Never);
+
+        let (invoker, _) =
+            authorizer.check_invocation_from_any_invoker(&devices, Rpc::Read, &[&rcan])?;
+        assert_eq!(invoker, devices[1]);
+
+        // The matching invoker still needs the capability.
+        let err = authorizer
+            .check_invocation_from_any_invoker(&devices, Rpc::All, &[&rcan])
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::CapabilityNotPermitted { proof: 0 }),
+            "{err}"
+        );
+
+        for invokers in [&[devices[0], devices[2]][..], &[]] {
+            let err = authorizer
+                .check_invocation_from_any_invoker(invokers, Rpc::Read, &[&rcan])
+                .unwrap_err();
+            assert!(matches!(err, RcanError::NoMatchingInvoker { .. }), "{err}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failing_proof_index() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let owner = service.verifying_key();
+        let root =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All).sign(Expires::Never);
+        let to_bob = Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Rpc::ReadWrite)
+            .sign(Expires::Never);
+        let authorizer = Authorizer::new(owner);
+        let check = |chain: &[&Rcan<Rpc>]| {
+            authorizer
+                .check_invocation_from(carol.verifying_key(), Rpc::Read, chain)
+                .unwrap_err()
+        };
+
+        // Carol's token is issued by Alice instead of Bob.
+        let skipping = Rcan::delegating_builder(&alice, carol.verifying_key(), owner, Rpc::Read)
+            .sign(Expires::Never);
+        let err = check(&[&root, &to_bob, &skipping]);
+        assert!(
+            matches!(err, RcanError::ProofIssuerMismatch { index: 2, .. }),
+            "{err}"
+        );
+        assert!(err.to_string().contains("proof #2"), "{err}");
+
+        let expired = Rcan::delegating_builder(&bob, carol.verifying_key(), owner, Rpc::Read)
+            .sign(Expires::At(0));
+        let err = check(&[&root, &to_bob, &expired]);
+        assert!(matches!(err, RcanError::Expired { index: 2, .. }), "{err}");
+
+        let foreign =
+            Rcan::delegating_builder(&bob, carol.verifying_key(), bob.verifying_key(), Rpc::Read)
+                .sign(Expires::Never);
+        let err = check(&[&root, &to_bob, &foreign]);
+        assert!(
+            matches!(err, RcanError::MissingDelegation { index: 2, .. }),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_rcan_invocation_verbose() {
+        /// A capability backed by a policy lookup that may be unavailable.
+        #[derive(Serialize)]
+        enum Remote {
+            Allow,
+            Unavailable,
+        }
+
+        impl Capability for Remote {
+            fn permits(&self, other: &Self) -> bool {
+                self.try_permits(other).unwrap_or(false)
+            }
+
+            fn try_permits(&self, _other: &Self) -> Result<bool, BoxError> {
+                match self {
+                    Remote::Allow => Ok(true),
+                    Remote::Unavailable => Err("policy source unavailable".into()),
+                }
+            }
+        }
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let owner = service.verifying_key();
+
+        let chain = [
+            Rcan::issuing_builder(&service, alice.verifying_key(), Remote::Unavailable)
+                .sign(Expires::Never),
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Remote::Allow)
+                .sign(Expires::Never),
+            Rcan::delegating_builder(&bob, carol.verifying_key(), owner, Remote::Unavailable)
+                .sign(Expires::Never),
+        ];
+        let chain: Vec<_> = chain.iter().collect();
+
+        let report = Authorizer::new(owner).check_invocation_from_verbose(
+            carol.verifying_key(),
+            Remote::Allow,
+            &chain,
+        );
+        assert!(!report.is_permitted());
+        assert!(report.chain_error.is_none());
+        assert!(report.denied.is_empty());
+        let failed: Vec<usize> = report.evaluation_errors.iter().map(|(i, _)| *i).collect();
+        assert_eq!(failed, [0, 2]);
+    }
+
+    #[test]
+    fn test_rcan_ord() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let bob = SigningKey::from_bytes(&[2u8; 32]).verifying_key();
+        let mint =
+            |audience, expires| Rcan::issuing_builder(&service, audience, Rpc::Read).sign(expires);
+
+        let mut rcans = [
+            mint(alice, Expires::Never),
+            mint(alice, Expires::At(20)),
+            mint(bob, Expires::At(10)),
+            mint(alice, Expires::At(10)),
+        ];
+        rcans.sort();
+
+        let (first, second) = if alice.as_bytes() < bob.as_bytes() {
+            (alice, bob)
+        } else {
+            (bob, alice)
+        };
+        let summary: Vec<_> = rcans
+            .iter()
+            .map(|r| (r.expires().clone(), *r.audience()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Expires::At(10), first),
+                (Expires::At(10), second),
+                (Expires::At(20), alice),
+                (Expires::Never, alice),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_capability() {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let mint = || {
+            Rcan::issuing_builder(&issuer, audience, String::from("bucket/a")).sign(Expires::Never)
+        };
+
+        let capability: String = mint().into_capability();
+        assert_eq!(capability, "bucket/a");
+
+        let payload = mint().into_payload();
+        assert_eq!(payload.capability(), "bucket/a");
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema() {
+        let schema = schemars::schema_for!(Payload<u8>);
+        let properties = &schema.as_value()["properties"];
+        for field in [
+            "issuer",
+            "audiences",
+            "capability_origin",
+            "capabilities",
+            "valid_until",
+            "valid_from",
+            "issued_at",
+            "max_delegation_depth",
+            "meta",
+        ] {
+            assert!(properties.get(field).is_some(), "missing {field}");
+        }
+        assert_eq!(properties["issuer"]["type"], "string");
+
+        let schema = schemars::schema_for!(Expires);
+        assert!(schema.as_value().to_string().contains("At"));
+    }
+
+    #[test]
+    fn test_verify_batch_tokens() {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let mut tokens: Vec<_> = (0..16u8)
+            .map(|i| {
+                let audience = SigningKey::from_bytes(&[i + 1; 32]).verifying_key();
+                Rcan::issuing_builder(&issuer, audience, Rpc::Read).sign(Expires::Never)
+            })
+            .collect();
+        assert!(Rcan::verify_batch_tokens(&tokens).iter().all(Result::is_ok));
+
+        tokens[5].payload.capabilities[0] = Rpc::All;
+        let results = Rcan::verify_batch_tokens(&tokens);
+        assert_eq!(results.len(), tokens.len());
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result.is_err(), i == 5, "token #{i}");
+        }
+
+        assert!(Rcan::<Rpc>::verify_batch_tokens(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_verify_batch() -> TestResult {
+        let issuers: Vec<_> = (0..4u8).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let tokens: Vec<_> = (0..64u8)
+            .map(|i| {
+                let issuer = &issuers[usize::from(i) % issuers.len()];
+                let audience = SigningKey::from_bytes(&[i.wrapping_add(100); 32]).verifying_key();
+                // Issued at a fixed time, so the bad signatures below are
+                // the same on every run.
+                Rcan::issuing_builder(issuer, audience, Rpc::Read)
+                    .with_meta("n", i.to_string())
+                    .issued_at(SystemTime::UNIX_EPOCH)
+                    .sign(Expires::Never)
+            })
+            .collect();
+        Rcan::verify_batch(&tokens)?;
+        Rcan::<Rpc>::verify_batch(&[])?;
+
+        // The batch rejects exactly when decoding one by one does, and
+        // names the same first bad token.
+        for tampered in [vec![0], vec![5], vec![17, 40], vec![63, 2]] {
+            let mut tokens = tokens.clone();
+            for &i in &tampered {
+                tokens[i].payload.capabilities[0] = Rpc::All;
+            }
+            let first_decode_failure = tokens
+                .iter()
+                .position(|token| Rcan::<Rpc>::decode(&token.encode()).is_err());
+            let err = Rcan::verify_batch(&tokens).unwrap_err();
+            let RcanError::InvalidBatchSignature { index, .. } = err else {
+                panic!("unexpected error {err}");
+            };
+            assert_eq!(Some(index), first_decode_failure);
+            assert_eq!(Some(index), tampered.iter().copied().min());
+        }
+
+        // Signatures `verify_strict` rejects are rejected by the batch,
+        // even those cofactored verification accepts.
+        let malleated = {
+            // s + l, the same scalar in a non-canonical encoding.
+            let mut l = [0u8; 32];
+            hex::decode_to_slice(
+                "edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010",
+                &mut l,
+            )?;
+            let mut token = tokens[3].clone();
+            let mut s = *token.signature.s_bytes();
+            let mut carry = 0u16;
+            for (s, l) in s.iter_mut().zip(l) {
+                let sum = u16::from(*s) + u16::from(l) + carry;
+                *s = sum as u8;
+                carry = sum >> 8;
+            }
+            token.signature = Signature::from_components(*token.signature.r_bytes(), s);
+            token
+        };
+        let mixed_order_key = {
+            // Signed for the key plus a point of order 8, which cancels
+            // out in some random combinations of a batch.
+            let mut torsion = [0u8; 32];
+            hex::decode_to_slice(
+                "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a",
+                &mut torsion,
+            )?;
+            let torsion = VerifyingKey::from_bytes(&torsion)?;
+            assert!(torsion.is_weak());
+            let key =
+                VerifyingKey::from(issuers[1].verifying_key().to_edwards() + torsion.to_edwards());
+            let mut token = tokens[1].clone();
+            token.payload.issuer = key;
+            let expanded = ed25519_dalek::hazmat::ExpandedSecretKey::from(issuers[1].as_bytes());
+            token.signature = ed25519_dalek::hazmat::raw_sign::<ed25519_dalek::Sha512>(
+                &expanded,
+                &signing_preimage(&token.payload, None),
+                &key,
+            );
+            token
+        };
+        let small_order = {
+            // The identity as key and R, with s = 0, which a batch
+            // accepts.
+            let mut identity = [0u8; 32];
+            identity[0] = 1;
+            let mut token = tokens[2].clone();
+            token.payload.issuer = VerifyingKey::from_bytes(&identity)?;
+            token.signature = Signature::from_components(identity, [0u8; 32]);
+            token
+        };
+        for bad in [malleated, mixed_order_key, small_order] {
+            assert!(Rcan::<Rpc>::decode(&bad.encode()).is_err());
+            for position in [0, 7, 16] {
+                let mut tokens = tokens[..16].to_vec();
+                tokens.insert(position, bad.clone());
+                let err = Rcan::verify_batch(&tokens).unwrap_err();
+                let RcanError::InvalidBatchSignature { index, .. } = err else {
+                    panic!("unexpected error {err}");
+                };
+                assert_eq!(index, position);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcan_invocation_with_policy() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let service_rcan =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read).sign(Expires::Never);
+        let service_auth = Authorizer::new(service.verifying_key());
+
+        let downgrade = |requested: &Rpc, granted: &Rpc| {
+            assert_eq!(requested, &Rpc::ReadWrite);
+            assert_eq!(granted, &Rpc::Read);
+            PolicyDecision::AllowDowngraded(Rpc::Read)
+        };
+        let authorized = service_auth.check_invocation_from_with_policy(
+            alice.verifying_key(),
+            Rpc::ReadWrite,
+            &[&service_rcan],
+            downgrade,
+        )?;
+        assert_eq!(authorized, Rpc::Read);
+
+        // Downgrading to something the chain doesn't permit still fails.
+        assert!(service_auth
+            .check_invocation_from_with_policy(
+                alice.verifying_key(),
+                Rpc::ReadWrite,
+                &[&service_rcan],
+                |_, _| PolicyDecision::AllowDowngraded(Rpc::All),
+            )
+            .is_err());
+        assert!(service_auth
+            .check_invocation_from_with_policy(
+                alice.verifying_key(),
+                Rpc::ReadWrite,
+                &[&service_rcan],
+                |_, _| PolicyDecision::Deny,
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_issuer_audience_is() {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]).verifying_key();
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&SigningKey::from_bytes(&[0u8; 32]), audience, Rpc::Read)
+            .sign(Expires::Never);
+
+        assert!(rcan.issuer_is(&issuer));
+        assert!(!rcan.issuer_is(&audience));
+        assert!(rcan.audience_is(&audience));
+        assert!(!rcan.audience_is(&issuer));
+    }
+
+    #[test]
+    fn test_rcan_invocation_from_at() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let service_rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .sign(Expires::valid_for_secs(60));
+        let service_auth = Authorizer::new(service.verifying_key());
+
+        let now = SystemTime::now();
+        let _ = service_auth.check_invocation_from_at(
+            now,
+            alice.verifying_key(),
+            Rpc::Read,
+            &[&service_rcan],
+        )?;
+        assert!(service_auth
+            .check_invocation_from_at(
+                now + Duration::from_secs(120),
+                alice.verifying_key(),
+                Rpc::Read,
+                &[&service_rcan],
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expiry_in_middle_of_chain() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .sign(Expires::At(2000));
+        let middle = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::ReadWrite,
+        )
+        .sign(Expires::At(1000));
+        let last = Rcan::delegating_builder(
+            &bob,
+            carol.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::At(2000));
+        let chain = [&root, &middle, &last];
+        let service_auth = Authorizer::new(service.verifying_key());
+
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let proof = service_auth.check_invocation_from_at(
+            at(1000),
+            carol.verifying_key(),
+            Rpc::Read,
+            &chain,
+        )?;
+        assert_eq!(proof.expires(), &Expires::At(1000));
+        assert!(matches!(
+            service_auth.check_invocation_from_at(
+                at(1001),
+                carol.verifying_key(),
+                Rpc::Read,
+                &chain
+            ),
+            Err(RcanError::Expired {
+                index: 1,
+                valid_until: Expires::At(1000)
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcan_issue_from_template() -> TestResult {
+        /// A namespace with an `{id}` placeholder.
+        struct PathTemplate(&'static str);
+
+        impl CapabilityTemplate<caps::NamespaceCapability> for PathTemplate {
+            type Param = str;
+
+            fn instantiate(&self, id: &str) -> caps::NamespaceCapability {
+                caps::NamespaceCapability::new(self.0.replace("{id}", id)).expect("valid id")
+            }
+        }
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let service_auth = Authorizer::new(service.verifying_key());
+
+        let tokens = Rcan::issuing_builder(&service, alice, PathTemplate("buckets.{id}.*"))
+            .issue_from_template(["a", "b"], Expires::Never);
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].capability().as_str(), "buckets.a.*");
+        assert_eq!(tokens[1].capability().as_str(), "buckets.b.*");
+
+        let read_a = caps::NamespaceCapability::new("buckets.a.read")?;
+        let _ = service_auth.check_invocation_from(alice, read_a.clone(), &[&tokens[0]])?;
+        assert!(service_auth
+            .check_invocation_from(alice, read_a, &[&tokens[1]])
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcan_multiple_capabilities() -> TestResult {
+        use caps::NamespaceCapability as Ns;
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let service_auth = Authorizer::new(service.verifying_key());
+
+        let grant = Rcan::issuing_builder(&service, alice.verifying_key(), Ns::new("a.read")?)
+            .add_capability(Ns::new("b.*")?)
+            .sign(Expires::Never);
+        assert_eq!(grant.capabilities().len(), 2);
+        assert_eq!(grant.capability(), &Ns::new("a.read")?);
+        assert_eq!(Rcan::decode(&grant.encode())?, grant);
+
+        // Either capability is enough, but only one has to permit.
+        for permitted in ["a.read", "b.write"] {
+            let _ = service_auth.check_invocation_from(
+                alice.verifying_key(),
+                Ns::new(permitted)?,
+                &[&grant],
+            )?;
+        }
+        let err = service_auth
+            .check_invocation_from(alice.verifying_key(), Ns::new("a.write")?, &[&grant])
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::CapabilityNotPermitted { proof: 0 }),
+            "{err}"
+        );
+
+        // Each delegated capability must be within one of the parent's.
+        let owner = service.verifying_key();
+        let delegation =
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Ns::new("b.write")?)
+                .add_capability(Ns::new("a.read")?)
+                .sign(Expires::Never);
+        assert!(grant.links_to(&delegation));
+        let escalation =
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Ns::new("b.write")?)
+                .add_capability(Ns::new("c.read")?)
+                .sign(Expires::Never);
+        assert!(!grant.links_to(&escalation));
+
+        // Decoding rejects tokens without any capability.
+        let mut empty = grant.clone();
+        empty.payload.capabilities.clear();
+        assert!(Rcan::<Ns>::decode(&empty.encode()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_request() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let bob = SigningKey::from_bytes(&[2u8; 32]).verifying_key();
+        let now = SystemTime::now();
+        let rcan = Rcan::issuing_builder(&service, alice, Rpc::ReadWrite)
+            .sign(Expires::At(unix_secs(now + Duration::from_secs(60))));
+
+        assert!(rcan.matches_request(&alice, &Rpc::Read, now));
+        assert!(!rcan.matches_request(&bob, &Rpc::Read, now));
+        assert!(!rcan.matches_request(&alice, &Rpc::All, now));
+        // expired
+        assert!(!rcan.matches_request(&alice, &Rpc::Read, now + Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn test_resolve_invocation() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.verifying_key();
+        let service_auth = Authorizer::new(owner);
+
+        let service_rcan =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All).sign(Expires::Never);
+        let alice_rcan = Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Rpc::Read)
+            .add_capability(Rpc::ReadWrite)
+            .sign(Expires::Never);
+
+        let resolved = service_auth.resolve_invocation_from(
+            bob.verifying_key(),
+            Rpc::ReadWrite,
+            &[&service_rcan, &alice_rcan],
+        )?;
+        let grant = resolved.grant.expect("delegated");
+        assert_eq!(grant.index, 1);
+        assert_eq!(grant.proof.issuer(), &alice.verifying_key());
+        assert_eq!(grant.capability, &Rpc::ReadWrite);
+
+        // The owner invoking its own capability has no grant.
+        let resolved = service_auth.resolve_invocation_from(owner, Rpc::All, &[])?;
+        assert!(resolved.grant.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_window() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let hour = 3600;
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .issued_at(at(1000))
+            .sign(Expires::At(1000 + 2 * hour));
+        let delegation = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .valid_from(NotBefore::At(1000 + hour + hour / 2))
+        .sign(Expires::Never);
+        let service_auth =
+            Authorizer::new(service.verifying_key()).with_max_window(Duration::from_secs(hour));
+
+        // The root alone is valid for two hours.
+        assert!(matches!(
+            service_auth.check_invocation_from_at(
+                at(2000),
+                alice.verifying_key(),
+                Rpc::Read,
+                &[&root]
+            ),
+            Err(RcanError::ValidityWindowExceeded { .. })
+        ));
+        // The delegation narrows the window to the last half hour.
+        let _ = service_auth.check_invocation_from_at(
+            at(1000 + 2 * hour),
+            bob.verifying_key(),
+            Rpc::Read,
+            &[&root, &delegation],
+        )?;
+        // Without an expiry the window is unbounded.
+        let forever =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All).sign(Expires::Never);
+        assert!(matches!(
+            service_auth.check_invocation_from(alice.verifying_key(), Rpc::Read, &[&forever]),
+            Err(RcanError::ValidityWindowExceeded { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_chain_depth() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let owner = service.verifying_key();
+        let keys: Vec<_> = (1..=17u8)
+            .map(|i| SigningKey::from_bytes(&[i; 32]))
+            .collect();
+        let mut chain = vec![
+            Rcan::issuing_builder(&service, keys[0].verifying_key(), Rpc::Read)
+                .sign(Expires::Never),
+        ];
+        for pair in keys.windows(2) {
+            chain.push(
+                Rcan::delegating_builder(&pair[0], pair[1].verifying_key(), owner, Rpc::Read)
+                    .sign(Expires::Never),
+            );
+        }
+        let chain: Vec<_> = chain.iter().collect();
+        assert_eq!(chain.len(), Authorizer::DEFAULT_MAX_DEPTH + 1);
+
+        // Exactly at the limit passes, one more proof fails.
+        let authorizer = Authorizer::new(owner);
+        let _ =
+            authorizer.check_invocation_from(keys[15].verifying_key(), Rpc::Read, &chain[..16])?;
+        let err = authorizer
+            .check_invocation_from(keys[16].verifying_key(), Rpc::Read, &chain)
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::ChainTooLong { max_depth: 16 }),
+            "{err}"
+        );
+
+        let authorizer = authorizer.with_max_depth(2);
+        let _ =
+            authorizer.check_invocation_from(keys[1].verifying_key(), Rpc::Read, &chain[..2])?;
+        assert!(authorizer
+            .check_invocation_from(keys[2].verifying_key(), Rpc::Read, &chain[..3])
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcan_valid_from() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let service_auth = Authorizer::new(service.verifying_key());
+
+        let now = SystemTime::now();
+        let activation = now + Duration::from_secs(60);
+        let scheduled = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .valid_from(NotBefore::At(unix_secs(activation)))
+            .sign(Expires::Never);
+        assert_eq!(Rcan::decode(&scheduled.encode())?, scheduled);
+
+        // Rejected until its activation instant, accepted from then on.
+        let err = service_auth
+            .check_invocation_from(alice.verifying_key(), Rpc::Read, &[&scheduled])
+            .unwrap_err();
+        assert!(
+            matches!(&err, RcanError::NotYetValid { index: 0, valid_from } if *valid_from == NotBefore::At(unix_secs(activation))),
+            "{err}"
+        );
+        let _ = service_auth.check_invocation_from_at(
+            activation,
+            alice.verifying_key(),
+            Rpc::Read,
+            &[&scheduled],
+        )?;
+
+        // A lapsed token expired instead, which calls for renewal.
+        let lapsed = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .sign(Expires::At(unix_secs(now) - 60));
+        let err = service_auth
+            .check_invocation_from(alice.verifying_key(), Rpc::Read, &[&lapsed])
+            .unwrap_err();
+        assert!(
+            matches!(&err, RcanError::Expired { index: 0, valid_until } if valid_until == lapsed.expires()),
+            "{err}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcan_aad() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::Read)
+            .sign_with_aad(Expires::Never, b"prod");
+        let encoded = rcan.encode();
+
+        assert_eq!(Rcan::<Rpc>::decode_with_aad(&encoded, b"prod")?, rcan);
+        assert!(Rcan::<Rpc>::decode_with_aad(&encoded, b"staging").is_err());
+        assert!(Rcan::<Rpc>::decode_with_aad(&encoded, b"").is_err());
+        assert!(Rcan::<Rpc>::decode(&encoded).is_err());
+
+        // A plain token doesn't verify as one with empty associated data.
+        let plain = Rcan::issuing_builder(&issuer, audience, Rpc::Read).sign(Expires::Never);
+        assert!(Rcan::<Rpc>::decode_with_aad(&plain.encode(), b"").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcan_invocation_with_meta() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+
+        let service_rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .with_meta("label", "service grant")
+            .sign(Expires::Never);
+        let friend_rcan = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .with_meta("request-id", "42")
+        .sign(Expires::valid_for_secs(60));
+        let friend_rcan = Rcan::<Rpc>::decode(&friend_rcan.encode())?;
+
+        let (proof, meta) = Authorizer::new(service.verifying_key())
+            .check_invocation_from_with_meta(
+                bob.verifying_key(),
+                Rpc::Read,
+                &[&service_rcan, &friend_rcan],
+            )?;
+        assert_eq!(proof.expires(), friend_rcan.expires());
+        let meta = meta.expect("non-empty chain");
+        assert_eq!(meta, &Meta::from([("request-id".into(), "42".into())]));
+
+        let (_, meta) = Authorizer::new(service.verifying_key()).check_invocation_from_with_meta(
+            service.verifying_key(),
+            Rpc::Read,
+            &[],
+        )?;
+        assert!(meta.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_self_delegation_is_issuing() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::delegating_builder(&issuer, audience, issuer.verifying_key(), Rpc::All)
+            .sign(Expires::Never);
+        assert_eq!(rcan.capability_origin(), &CapabilityOrigin::Issuer);
+        assert_eq!(
+            Rcan::<Rpc>::decode_strict(&rcan.encode())?.capability_origin(),
+            &CapabilityOrigin::Issuer
+        );
+
+        // Hand-craft the redundant form, which only strict decoding rejects.
+        let payload = Payload {
+            capability_origin: CapabilityOrigin::Delegation(issuer.verifying_key()),
+            ..rcan.into_payload()
+        };
+        let redundant = Rcan::sign_payload(&issuer, payload).encode();
+        assert!(Rcan::<Rpc>::decode(&redundant).is_ok());
+        assert!(Rcan::<Rpc>::decode_strict(&redundant).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_meta_is_signed() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .with_meta("trace", r#"{"span":"a1"}"#)
+            .sign(Expires::Never);
+        let encoded = rcan.encode();
+        assert_eq!(Rcan::<Rpc>::decode(&encoded)?.meta(), rcan.meta());
+
+        // Flip a byte of the value, keeping it valid UTF-8.
+        let value = br#"{"span":"a1"}"#;
+        let at = encoded
+            .windows(value.len())
+            .position(|window| window == value)
+            .expect("meta value in encoding");
+        let mut tampered = encoded.clone();
+        tampered[at + 10] ^= 0x01;
+        assert!(Rcan::<Rpc>::decode(&tampered).is_err());
+
+        let mut edited = rcan.clone();
+        edited.payload.meta.insert("trace".into(), "forged".into());
+        let err = edited.verify_self().unwrap_err();
+        assert!(matches!(err, RcanError::InvalidSignature(_)), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_strict_non_minimal_varint() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::Read)
+            .issued_at(SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+            .sign(Expires::Never);
+        let encoded = rcan.encode();
+
+        // Version, the length prefixed issuer, one length prefixed
+        // audience, origin, capability count and capability, valid_until
+        // and valid_from, then issued_at: 1.
+        let at = 1 + 33 + 1 + 33 + 5;
+        assert_eq!(encoded[at], 0x01);
+        // Encode issued_at as a two byte varint instead.
+        let mut padded = encoded.clone();
+        padded.splice(at..=at, [0x81, 0x00]);
+
+        assert_eq!(Rcan::<Rpc>::decode(&padded)?, rcan);
+        assert!(matches!(
+            Rcan::<Rpc>::decode_strict(&padded),
+            Err(RcanError::NonCanonical)
+        ));
+        assert_eq!(Rcan::<Rpc>::decode_strict(&encoded)?, rcan);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_with_key() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let other = SigningKey::from_bytes(&[2u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::Read).sign(Expires::Never);
+        rcan.verify_with_key(&issuer.verifying_key())?;
+        assert!(rcan.verify_with_key(&other.verifying_key()).is_err());
+
+        // Swapping the embedded issuer breaks the signature.
+        let swapped = Rcan {
+            payload: Payload {
+                issuer: other.verifying_key(),
+                ..rcan.payload
+            },
+            signature: rcan.signature,
+        };
+        assert!(swapped.verify_with_key(&other.verifying_key()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_effective_capability_intersection() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let authorizer = Authorizer::new(service.verifying_key());
+
+        let read =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read).sign(Expires::Never);
+        let read_write = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .sign(Expires::Never);
+
+        let effective = authorizer.effective_capability_intersection(
+            alice.verifying_key(),
+            &[&read],
+            &[&read_write],
+        )?;
+        assert_eq!(effective, Some(Rpc::Read));
+
+        // Both chains still have to be valid for the invoker.
+        assert!(authorizer
+            .effective_capability_intersection(service.verifying_key(), &[&read], &[&read_write])
+            .is_err());
+        let err = authorizer
+            .effective_capability_intersection::<Rpc>(service.verifying_key(), &[], &[])
+            .unwrap_err();
+        assert!(matches!(err, RcanError::NothingToIntersect), "{err}");
+        // An empty chain only holds for the authorizer's identity, and
+        // doesn't restrict the other one.
+        assert!(authorizer
+            .effective_capability_intersection(alice.verifying_key(), &[&read], &[])
+            .is_err());
+        let own = Rcan::issuing_builder(&service, service.verifying_key(), Rpc::Read)
+            .sign(Expires::Never);
+        assert_eq!(
+            authorizer.effective_capability_intersection(service.verifying_key(), &[&own], &[])?,
+            Some(Rpc::Read)
+        );
+
+        // The capabilities of a proof are combined, not intersected with
+        // each other.
+        use caps::TogglesCapability;
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let toggles = |names: &[&str]| {
+            TogglesCapability(names.iter().map(|name| (name.to_string(), true)).collect())
+        };
+        let root =
+            Rcan::issuing_builder(&service, alice.verifying_key(), toggles(&["a", "b", "c"]))
+                .sign(Expires::Never);
+        let delegation = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            toggles(&["a"]),
+        )
+        .add_capability(toggles(&["b"]))
+        .sign(Expires::Never);
+        let direct = Rcan::issuing_builder(&service, bob.verifying_key(), toggles(&["b", "c"]))
+            .sign(Expires::Never);
+        let effective = authorizer.effective_capability_intersection(
+            bob.verifying_key(),
+            &[&root, &delegation],
+            &[&direct],
+        )?;
+        assert_eq!(effective, Some(toggles(&["b"])));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_effective_capability() -> TestResult {
+        use caps::TogglesCapability;
+
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.verifying_key();
+        let authorizer = Authorizer::new(owner);
+        let toggles = |names: &[&str]| {
+            TogglesCapability(names.iter().map(|name| (name.to_string(), true)).collect())
+        };
+
+        let root =
+            Rcan::issuing_builder(&service, alice.verifying_key(), toggles(&["a", "b", "c"]))
+                .sign(Expires::Never);
+        let delegation =
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, toggles(&["a"]))
+                .add_capability(toggles(&["b"]))
+                .sign(Expires::Never);
+        let effective =
+            authorizer.effective_capability(bob.verifying_key(), &[&root, &delegation])?;
+        assert_eq!(effective, Some(toggles(&["a", "b"])));
+        assert_eq!(
+            authorizer.effective_capability(alice.verifying_key(), &[&root])?,
+            Some(toggles(&["a", "b", "c"]))
+        );
+
+        // The capabilities of a proof are merged before attenuating.
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+            .add_capability(Rpc::Read)
+            .sign(Expires::Never);
+        let delegation =
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Rpc::ReadWrite)
+                .sign(Expires::Never);
+        let effective =
+            authorizer.effective_capability(bob.verifying_key(), &[&root, &delegation])?;
+        assert_eq!(effective, Some(Rpc::ReadWrite));
+
+        // The chain still has to be valid for the invoker.
+        let err = authorizer
+            .effective_capability(alice.verifying_key(), &[&root, &delegation])
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::ChainEndpointMismatch { .. }),
+            "{err}"
+        );
+        let err = authorizer
+            .effective_capability::<Rpc>(owner, &[])
+            .unwrap_err();
+        assert!(matches!(err, RcanError::EmptyChain), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_audience() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let group = SigningKey::from_bytes(&[4u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let authorizer = Authorizer::new(service.verifying_key());
+
+        let rcan = Rcan::issuing_builder(&service, group.verifying_key(), Rpc::ReadWrite)
+            .audience_group(GroupKey {
+                key: group.verifying_key(),
+                members: vec![alice.verifying_key(), bob.verifying_key()],
+            })
+            .sign(Expires::Never);
+        let rcan = Rcan::<Rpc>::decode(&rcan.encode())?;
+        assert!(rcan.audience_admits(&bob.verifying_key()));
+
+        let _ = authorizer.check_invocation_from(alice.verifying_key(), Rpc::Read, &[&rcan])?;
+        let _ = authorizer.check_invocation_from(group.verifying_key(), Rpc::Read, &[&rcan])?;
+        assert!(authorizer
+            .check_invocation_from(carol.verifying_key(), Rpc::Read, &[&rcan])
+            .is_err());
+
+        // Members can't delegate on the group's behalf.
+        let delegation = Rcan::delegating_builder(
+            &alice,
+            carol.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
         )
+        .sign(Expires::Never);
+        assert!(authorizer
+            .check_invocation_from(carol.verifying_key(), Rpc::Read, &[&rcan, &delegation])
+            .is_err());
+
+        Ok(())
     }
 
-    pub fn is_valid_at(&self, time: SystemTime) -> bool {
-        let time = time
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .expect("time must be after UNIX_EPOCH")
-            .as_secs();
-        match self {
-            Expires::Never => true,
-            Expires::At(expiry) => *expiry >= time,
+    #[test]
+    fn test_multiple_audiences() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let dave = SigningKey::from_bytes(&[4u8; 32]);
+        let owner = service.verifying_key();
+        let authorizer = Authorizer::new(owner);
+        let replicas = [
+            alice.verifying_key(),
+            bob.verifying_key(),
+            carol.verifying_key(),
+        ];
+
+        let rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .add_audiences([
+                bob.verifying_key(),
+                carol.verifying_key(),
+                bob.verifying_key(),
+            ])
+            .sign(Expires::Never);
+        let rcan = Rcan::<Rpc>::decode_strict(&rcan.encode())?;
+        assert_eq!(rcan.audiences(), &replicas);
+        for replica in replicas {
+            let _ = authorizer.check_invocation_from(replica, Rpc::Read, &[&rcan])?;
         }
-    }
-}
+        let err = authorizer
+            .check_invocation_from(dave.verifying_key(), Rpc::Read, &[&rcan])
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::ChainEndpointMismatch { .. }),
+            "{err}"
+        );
 
-#[cfg(test)]
-mod test {
-    use testresult::TestResult;
+        // Repeating the audience doesn't change the token.
+        let single = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .add_audiences([alice.verifying_key()])
+            .issued_at(SystemTime::UNIX_EPOCH)
+            .sign(Expires::Never);
+        let plain = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .issued_at(SystemTime::UNIX_EPOCH)
+            .sign(Expires::Never);
+        assert_eq!(single, plain);
+        assert_eq!(plain.audiences(), &[alice.verifying_key()]);
 
-    use super::*;
+        // Any audience may issue the next link, which is then only
+        // addressed to its own audience.
+        let delegation = Rcan::delegating_builder(&bob, dave.verifying_key(), owner, Rpc::Read)
+            .sign(Expires::Never);
+        assert!(rcan.links_to(&delegation));
+        let chain = [&rcan, &delegation];
+        let _ = authorizer.check_invocation_from(dave.verifying_key(), Rpc::Read, &chain)?;
+        let err = authorizer
+            .check_invocation_from(alice.verifying_key(), Rpc::Read, &chain)
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::ChainEndpointMismatch { .. }),
+            "{err}"
+        );
+        let err = authorizer
+            .check_invocation_from(dave.verifying_key(), Rpc::Read, &[&delegation])
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::ProofIssuerMismatch { index: 0, .. }),
+            "{err}"
+        );
 
-    #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
-    enum Rpc {
-        Read,
-        ReadWrite,
-        /// Read, ReadWrite, and any "future ones" that we might not have thought of yet.
-        All,
-    }
+        // Every audience of a delegation must be on its parent's allow-list.
+        let restricted = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .delegation_audience_allowlist([bob.verifying_key()])
+            .sign(Expires::Never);
+        let fanned_out = Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Rpc::Read)
+            .add_audiences([dave.verifying_key()])
+            .sign(Expires::Never);
+        assert!(!restricted.links_to(&fanned_out));
+        let err = authorizer
+            .check_invocation_from(bob.verifying_key(), Rpc::Read, &[&restricted, &fanned_out])
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::AudienceNotAllowed { index: 1, ref audience } if audience == dave.verifying_key().as_bytes()),
+            "{err}"
+        );
 
-    impl Capability for Rpc {
-        fn permits(&self, other: &Self) -> bool {
-            match (self, other) {
-                // `All` permits all RPC operations, by definition
-                (Rpc::All, _) => true,
-                // `ReadWrite` permits `Read` and `ReadWrite`, but not `All` (which may be extended later to include more caps)
-                (Rpc::ReadWrite, Rpc::ReadWrite | Rpc::Read) => true,
-                (Rpc::ReadWrite, _) => false,
-                // `Read` only permits `Read`
-                (Rpc::Read, Rpc::Read) => true,
-                (Rpc::Read, _) => false,
-            }
+        // Tokens without audiences or with a repeated one don't decode.
+        let hand_crafted = |audiences| {
+            let payload = Payload {
+                audiences,
+                ..plain.payload.clone()
+            };
+            Rcan::sign_payload(&service, payload)
+        };
+        for audiences in [
+            vec![],
+            vec![
+                alice.verifying_key(),
+                bob.verifying_key(),
+                alice.verifying_key(),
+            ],
+        ] {
+            let rcan = hand_crafted(audiences);
+            let err = Rcan::<Rpc>::decode(&rcan.encode()).unwrap_err();
+            assert!(matches!(err, RcanError::Decode(_)), "{err}");
+            #[cfg(feature = "json")]
+            assert!(Rcan::<Rpc>::from_json(&rcan.to_json()).is_err());
         }
+
+        Ok(())
     }
 
     #[test]
-    fn test_simple_capabilitys() {
-        assert!(Rpc::Read.permits(&Rpc::Read));
-        assert!(Rpc::ReadWrite.permits(&Rpc::Read));
-        assert!(Rpc::ReadWrite.permits(&Rpc::ReadWrite),);
-        assert!(!Rpc::Read.permits(&Rpc::ReadWrite));
-        assert!(!Rpc::Read.permits(&Rpc::All));
-        assert!(Rpc::All.permits(&Rpc::All));
-        assert!(Rpc::All.permits(&Rpc::Read));
-        assert!(Rpc::All.permits(&Rpc::ReadWrite));
+    fn test_to_builder() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::ReadWrite)
+            .max_delegation_depth(1)
+            .sign(Expires::At(1000));
+
+        let extended = rcan
+            .to_builder(&issuer)
+            .with_meta("renewed", "yes")
+            .sign(Expires::At(2000));
+        let extended = Rcan::<Rpc>::decode(&extended.encode())?;
+        assert_eq!(extended.expires(), &Expires::At(2000));
+        assert_eq!(extended.capability(), rcan.capability());
+        assert_eq!(extended.remaining_delegations_possible(), Some(1));
+        assert_eq!(
+            extended.meta().get("renewed").map(String::as_str),
+            Some("yes")
+        );
+        assert_ne!(extended.signature, rcan.signature);
+
+        Ok(())
     }
 
     #[test]
-    fn test_rcan_encoding() -> TestResult {
-        let issuer = SigningKey::from_bytes(&[0u8; 32]);
-        let audience = SigningKey::from_bytes(&[1u8; 32]);
-        let rcan = Rcan::issuing_builder(&issuer, audience.verifying_key(), Rpc::ReadWrite)
+    fn test_typed_authorizer() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let authorizer: TypedAuthorizer<Rpc> = TypedAuthorizer::new(service.verifying_key());
+        assert!(authorizer.authorizer() == &Authorizer::new(service.verifying_key()));
+
+        let rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
             .sign(Expires::Never);
+        let _ = authorizer.check_invocation_from(alice.verifying_key(), Rpc::Read, &[&rcan])?;
+        assert!(authorizer
+            .check_invocation_from(alice.verifying_key(), Rpc::All, &[&rcan])
+            .is_err());
 
-        println!("{}", hex::encode(rcan.encode()));
-        println!(
-            "{}",
-            hex::encode(postcard::to_allocvec(&rcan.signature).unwrap())
+        Ok(())
+    }
+
+    #[test]
+    fn test_delegation_audience_allowlist() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let authorizer = Authorizer::new(service.verifying_key());
+
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .delegation_audience_allowlist([bob.verifying_key()])
+            .sign(Expires::Never);
+        let root = Rcan::<Rpc>::decode(&root.encode())?;
+        assert_eq!(
+            root.delegation_audience_allowlist(),
+            Some(&[bob.verifying_key()][..])
         );
 
-        let expected: String = [
-            // Version
-            "01",
-            // Issuer
-            "203b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29",
-            // Audience
-            "208a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c",
-            // Capability Origin: Issuer
-            "00",
-            // capability: Rpc::ReadWrite
-            "01",
-            // Expires::Never
-            "00",
-            // Signature
-            "54675ed0b6ba3a830fe24ec8523f776fa43001edfe4cc9e3bd639009a2058b1805de5e05958b46c03b423ed5d1c72acaab48a9f3bf8db2402c82295f085df404",
-        ]
-        .join("");
+        let to_bob = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+        let _ =
+            authorizer.check_invocation_from(bob.verifying_key(), Rpc::Read, &[&root, &to_bob])?;
+        assert!(root.links_to(&to_bob));
+
+        let to_carol = Rcan::delegating_builder(
+            &alice,
+            carol.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::Never);
+        assert!(authorizer
+            .check_invocation_from(carol.verifying_key(), Rpc::Read, &[&root, &to_carol])
+            .is_err());
+        assert!(!root.links_to(&to_carol));
 
-        assert_eq!(hex::encode(rcan.encode()), expected);
-        assert_eq!(Rcan::decode(&rcan.encode())?, rcan);
         Ok(())
     }
 
     #[test]
-    fn deserialize_rejects_forged_signature() {
+    fn test_roundtrip_macro() {
         let issuer = SigningKey::from_bytes(&[0u8; 32]);
-        let audience = SigningKey::from_bytes(&[1u8; 32]);
-        let rcan = Rcan::issuing_builder(&issuer, audience.verifying_key(), Rpc::ReadWrite)
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::ReadWrite)
+            .with_meta("request", "42")
+            .sign(Expires::At(1000));
+        crate::test_roundtrip!(rcan);
+    }
+
+    #[test]
+    fn test_issuance_floor() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let floor = SystemTime::now() - Duration::from_secs(60);
+        let authorizer = Authorizer::new(service.verifying_key()).with_issuance_floor(floor);
+
+        let before = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read)
+            .issued_at(floor - Duration::from_secs(1))
             .sign(Expires::Never);
+        let err = authorizer
+            .check_invocation_from(alice.verifying_key(), Rpc::Read, &[&before])
+            .unwrap_err();
+        let RcanError::IssuedBeforeFloor {
+            index,
+            issued_at,
+            floor,
+        } = err
+        else {
+            panic!("expected a floor error, got {err}");
+        };
+        assert_eq!(index, 0);
+        assert_eq!(issued_at + 1, floor);
 
-        // A genuine token round-trips through serde.
-        let mut wire = postcard::to_stdvec(&rcan).unwrap();
-        assert_eq!(postcard::from_bytes::<Rcan<Rpc>>(&wire).unwrap(), rcan);
+        let after =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read).sign(Expires::Never);
+        let _ = authorizer.check_invocation_from(alice.verifying_key(), Rpc::Read, &[&after])?;
 
-        // The trailing bytes are the signature. Zeroing them must make
-        // deserialization fail rather than yield an unverified token.
-        let n = wire.len();
-        wire[n - SIGNATURE_LENGTH..].fill(0);
-        assert!(postcard::from_bytes::<Rcan<Rpc>>(&wire).is_err());
+        // An old token re-signed after the floor is accepted again.
+        let renewed = before.to_builder(&service).sign(Expires::Never);
+        let _ = authorizer.check_invocation_from(alice.verifying_key(), Rpc::Read, &[&renewed])?;
+
+        Ok(())
     }
 
     #[test]
-    fn test_rcan_invocation() -> TestResult {
+    fn test_verify_resolved() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let stranger = SigningKey::from_bytes(&[2u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let directory = [issuer.verifying_key()];
+        let trusted = |key: &VerifyingKey| directory.contains(key);
+
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::Read).sign(Expires::Never);
+        rcan.verify_resolved(&trusted)?;
+
+        // Validly signed, but by a key the directory doesn't know.
+        let untrusted = Rcan::issuing_builder(&stranger, audience, Rpc::Read).sign(Expires::Never);
+        untrusted.verify_self()?;
+        assert!(untrusted.verify_resolved(&trusted).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invocation_proof() -> TestResult {
         let service = SigningKey::from_bytes(&[0u8; 32]);
         let alice = SigningKey::from_bytes(&[1u8; 32]);
         let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let authorizer = Authorizer::new(service.verifying_key());
 
-        // The service gives alice access to everything for 60 seconds
-        let service_rcan = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
-            .sign(Expires::valid_for(Duration::from_secs(60)));
-        // alice gives attenuated (only read access) to bob, but doesn't care for how long still
-        let friend_rcan = Rcan::delegating_builder(
+        // An action that can only run with evidence of a verified invocation.
+        fn guarded_action(proof: &InvocationProof) -> &Expires {
+            proof.expires()
+        }
+
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .sign(Expires::At(u64::MAX));
+        let delegation = Rcan::delegating_builder(
+            &alice,
+            bob.verifying_key(),
+            service.verifying_key(),
+            Rpc::Read,
+        )
+        .sign(Expires::At(u64::MAX - 1));
+
+        let proof = authorizer.check_invocation_from(
+            bob.verifying_key(),
+            Rpc::Read,
+            &[&root, &delegation],
+        )?;
+        assert_eq!(guarded_action(&proof), &Expires::At(u64::MAX - 1));
+
+        // Owners invoking their own capability aren't limited by any proof.
+        let proof = authorizer.check_invocation_from(service.verifying_key(), Rpc::All, &[])?;
+        assert_eq!(guarded_action(&proof), &Expires::Never);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_confirm_holder() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let bob = SigningKey::from_bytes(&[2u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, alice, Rpc::Read).sign(Expires::Never);
+
+        rcan.confirm_holder(&alice)?;
+        assert!(rcan.confirm_holder(&bob).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcan_id() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::Read).sign(Expires::Never);
+
+        let decoded = Rcan::<Rpc>::decode(&rcan.encode())?;
+        assert_eq!(decoded.id(), rcan.id());
+        assert_eq!(Rcan::<Rpc>::decode(&decoded.encode())?.id(), rcan.id());
+
+        let other = Rcan::issuing_builder(&issuer, audience, Rpc::All).sign(Expires::Never);
+        assert_ne!(other.id(), rcan.id());
+
+        let id = rcan.id();
+        assert_eq!(id.to_string().len(), 64);
+        assert_eq!(id.to_string().parse::<RcanId>()?, id);
+        assert!("not hex".parse::<RcanId>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_revocations() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let root = Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::ReadWrite)
+            .sign(Expires::Never);
+        let delegation = Rcan::delegating_builder(
             &alice,
             bob.verifying_key(),
             service.verifying_key(),
             Rpc::Read,
         )
         .sign(Expires::Never);
-        // bob can now pass the authorization test for the service
-        let service_auth = Authorizer::new(service.verifying_key());
-        assert!(service_auth
-            .check_invocation_from(
-                bob.verifying_key(),
-                Rpc::Read,
-                &[&service_rcan, &friend_rcan],
-            )
-            .is_ok());
+        let chain = [&root, &delegation];
 
-        // but bob doesn't have read-write access
-        assert!(service_auth
-            .check_invocation_from(
-                bob.verifying_key(),
-                Rpc::ReadWrite,
-                &[&service_rcan, &friend_rcan]
-            )
-            .is_err());
+        assert_eq!(root.id(), Rcan::<Rpc>::decode(&root.encode())?.id());
+        assert_ne!(root.id(), delegation.id());
+
+        let revoked = delegation.id();
+        let authorizer =
+            Authorizer::new(service.verifying_key()).with_revocations(move |id| *id == revoked);
+        assert!(matches!(
+            authorizer.check_invocation_from(bob.verifying_key(), Rpc::Read, &chain),
+            Err(RcanError::Revoked { index: 1 })
+        ));
+        // The root alone is still valid.
+        let _ = authorizer.check_invocation_from(alice.verifying_key(), Rpc::Read, &[&root])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_and_split() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::ReadWrite)
+            .sign(Expires::valid_for_secs(60));
+
+        let (holder, capability) = rcan.verify_and_split(SystemTime::now())?;
+        assert_eq!(holder, &audience);
+        assert_eq!(capability, &Rpc::ReadWrite);
+
+        let later = SystemTime::now() + Duration::from_secs(120);
+        assert!(matches!(
+            rcan.verify_and_split(later),
+            Err(RcanError::Expired { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resign_as() -> TestResult {
+        let old_issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let new_issuer = SigningKey::from_bytes(&[9u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&old_issuer, audience, Rpc::ReadWrite)
+            .with_meta("label", "migrated")
+            .sign(Expires::At(1000));
+
+        let resigned = rcan.resign_as(&new_issuer, None);
+        let resigned = Rcan::<Rpc>::decode(&resigned.encode())?;
+        assert_eq!(resigned.issuer(), &new_issuer.verifying_key());
+        assert_eq!(resigned.audience(), rcan.audience());
+        assert_eq!(resigned.capability(), rcan.capability());
+        assert_eq!(resigned.capability_origin(), rcan.capability_origin());
+        assert_eq!(resigned.expires(), rcan.expires());
+        assert_eq!(resigned.meta(), rcan.meta());
+
+        let renewed = rcan.resign_as(&new_issuer, Some(Expires::Never));
+        let renewed = Rcan::<Rpc>::decode(&renewed.encode())?;
+        assert_eq!(renewed.expires(), &Expires::Never);
+
+        let _ = Authorizer::new(new_issuer.verifying_key()).check_invocation_from(
+            audience,
+            Rpc::Read,
+            &[&renewed],
+        )?;
 
         Ok(())
     }
@@ -614,4 +5209,63 @@ mod test {
         let future = now + Duration::from_secs(61);
         assert!(!rcan.expires().is_valid_at(future));
     }
+
+    #[test]
+    fn test_expiry_at_block() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::All).sign(Expires::AtBlock(100));
+        let rcan = Rcan::<Rpc>::decode(&rcan.encode())?;
+
+        assert!(rcan.expires().is_valid_at(Position::Block(99)));
+        assert!(rcan.expires().is_valid_at(Position::Block(100)));
+        assert!(!rcan.expires().is_valid_at(Position::Block(101)));
+        // Block heights say nothing about wall-clock time.
+        assert!(!rcan.expires().is_valid_at(SystemTime::now()));
+        assert!(!Expires::At(u64::MAX).is_valid_at(Position::Block(0)));
+
+        let authorizer = Authorizer::new(issuer.verifying_key());
+        let proof = authorizer.check_invocation_from_at(
+            Position::Block(99),
+            audience,
+            Rpc::Read,
+            &[&rcan],
+        )?;
+        assert_eq!(proof.expires(), &Expires::AtBlock(100));
+        assert!(matches!(
+            authorizer.check_invocation_from_at(
+                Position::Block(101),
+                audience,
+                Rpc::Read,
+                &[&rcan]
+            ),
+            Err(RcanError::Expired { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expires_earliest_latest() {
+        let mixed = [Expires::At(5), Expires::Never, Expires::At(10)];
+        assert_eq!(Expires::earliest(mixed.clone()), Expires::At(5));
+        assert_eq!(Expires::latest(mixed), Expires::Never);
+
+        let timed = [Expires::At(10), Expires::At(5)];
+        assert_eq!(Expires::latest(timed), Expires::At(10));
+
+        assert_eq!(Expires::earliest([]), Expires::Never);
+        assert_eq!(Expires::latest([]), Expires::Never);
+    }
+
+    #[test]
+    fn test_valid_for_secs() {
+        let Expires::At(from_secs) = Expires::valid_for_secs(60) else {
+            panic!("expected an expiry time");
+        };
+        let Expires::At(from_duration) = Expires::valid_for(Duration::from_secs(60)) else {
+            panic!("expected an expiry time");
+        };
+        assert!(from_secs.abs_diff(from_duration) <= 1);
+    }
 }
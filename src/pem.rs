@@ -0,0 +1,185 @@
+//! PEM encoding of [`Rcan`] tokens and [`ProofChain`]s, for moving them
+//! around by hand, e.g. in files or by copy and paste.
+//!
+//! Each token is one `RCAN` block over the exact bytes of
+//! [`Rcan::encode`]. A chain is its tokens' blocks one after another, root
+//! first. Text outside of blocks is ignored, as in RFC 7468.
+
+use alloc::{format, string::String, vec::Vec};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{ProofChain, Rcan, RcanError, Result};
+
+/// The PEM label of a token.
+const LABEL: &str = "RCAN";
+
+/// Length of the base64 lines of a block.
+const LINE_LEN: usize = 64;
+
+impl<C> Rcan<C> {
+    /// Encodes the token as a single `RCAN` PEM block.
+    pub fn to_pem(&self) -> String
+    where
+        C: Serialize,
+    {
+        let mut pem = String::new();
+        write_block(&mut pem, &self.encode());
+        pem
+    }
+
+    /// Decodes a token from a PEM document holding exactly one `RCAN`
+    /// block, and verifies its signature.
+    pub fn from_pem(pem: &str) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        match blocks(pem)?.as_slice() {
+            [bytes] => Self::decode(bytes),
+            blocks => Err(RcanError::Malformed(format!(
+                "expected one {LABEL} block, found {}",
+                blocks.len()
+            ))),
+        }
+    }
+}
+
+impl<C> ProofChain<C> {
+    /// Encodes the chain as one `RCAN` PEM block per token, root first.
+    pub fn to_pem(&self) -> String
+    where
+        C: Serialize,
+    {
+        let mut pem = String::new();
+        for proof in self.proofs() {
+            write_block(&mut pem, &proof.encode());
+        }
+        pem
+    }
+
+    /// Decodes a chain from consecutive `RCAN` PEM blocks, root first,
+    /// verifying each token's signature.
+    ///
+    /// Like deserializing a chain, this doesn't check how the tokens link
+    /// together, call [`ProofChain::validate`] on chains from untrusted
+    /// sources. Fails with [`RcanError::EmptyChain`] if there are no
+    /// blocks.
+    pub fn from_pem(pem: &str) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let proofs = blocks(pem)?
+            .iter()
+            .map(|bytes| Rcan::decode(bytes))
+            .collect::<Result<Vec<_>>>()?;
+        if proofs.is_empty() {
+            return Err(RcanError::EmptyChain);
+        }
+        Ok(Self::new(proofs))
+    }
+}
+
+/// Appends a PEM block of `bytes` to `pem`.
+fn write_block(pem: &mut String, bytes: &[u8]) {
+    let encoded = STANDARD.encode(bytes);
+    pem.push_str(&format!("-----BEGIN {LABEL}-----\n"));
+    // base64 is ASCII, so chunks are on char boundaries.
+    for line in encoded.as_bytes().chunks(LINE_LEN) {
+        pem.push_str(core::str::from_utf8(line).expect("base64 is ASCII"));
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {LABEL}-----\n"));
+}
+
+/// The decoded contents of the `RCAN` blocks in `pem`, in order.
+fn blocks(pem: &str) -> Result<Vec<Vec<u8>>> {
+    let begin = format!("-----BEGIN {LABEL}-----");
+    let end = format!("-----END {LABEL}-----");
+    let mut blocks = Vec::new();
+    let mut block: Option<String> = None;
+    for line in pem.lines().map(str::trim) {
+        match &mut block {
+            None if line == begin => block = Some(String::new()),
+            None => {}
+            Some(encoded) if line == end => {
+                let bytes = STANDARD
+                    .decode(&*encoded)
+                    .map_err(|err| RcanError::Malformed(format!("decoding PEM: {err}")))?;
+                blocks.push(bytes);
+                block = None;
+            }
+            Some(_) if line.starts_with("-----") => {
+                return Err(RcanError::Malformed(format!(
+                    "unexpected PEM boundary in {LABEL} block: {line}"
+                )));
+            }
+            Some(encoded) => encoded.push_str(line),
+        }
+    }
+    if block.is_some() {
+        return Err(RcanError::Malformed(format!("unterminated {LABEL} block")));
+    }
+    Ok(blocks)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use ed25519_dalek::SigningKey;
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::{test::Rpc, Expires};
+
+    #[test]
+    fn test_pem_roundtrip() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let owner = service.verifying_key();
+        let chain = ProofChain::new(vec![
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All)
+                .with_meta("ticket", "42")
+                .sign(Expires::Never),
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Rpc::ReadWrite)
+                .sign(Expires::Never),
+            Rcan::delegating_builder(&bob, carol.verifying_key(), owner, Rpc::Read)
+                .sign(Expires::Never),
+        ]);
+
+        let pem = chain.to_pem();
+        assert_eq!(pem.matches("-----BEGIN RCAN-----").count(), 3);
+        assert!(pem.lines().all(|line| line.len() <= LINE_LEN));
+        let decoded = ProofChain::<Rpc>::from_pem(&pem)?;
+        assert_eq!(decoded, chain);
+        decoded.validate()?;
+
+        // Text around the blocks is ignored.
+        let annotated = format!("root first\n\n{pem}\nend of chain\n");
+        assert_eq!(ProofChain::<Rpc>::from_pem(&annotated)?, chain);
+
+        // Single tokens
+        let root = &chain.proofs()[0];
+        assert_eq!(Rcan::<Rpc>::from_pem(&root.to_pem())?, *root);
+        let err = Rcan::<Rpc>::from_pem(&pem).unwrap_err();
+        assert!(matches!(err, RcanError::Malformed(_)), "{err}");
+
+        // Every block is verified.
+        let mut forged = chain.proofs()[1].encode();
+        let n = forged.len();
+        forged[n - 1] ^= 1;
+        let mut tampered = String::new();
+        write_block(&mut tampered, &chain.proofs()[0].encode());
+        write_block(&mut tampered, &forged);
+        assert!(ProofChain::<Rpc>::from_pem(&tampered).is_err());
+
+        let err = ProofChain::<Rpc>::from_pem("").unwrap_err();
+        assert!(matches!(err, RcanError::EmptyChain), "{err}");
+        let unterminated = pem.trim_end().trim_end_matches("-----END RCAN-----");
+        let err = ProofChain::<Rpc>::from_pem(unterminated).unwrap_err();
+        assert!(matches!(err, RcanError::Malformed(_)), "{err}");
+
+        Ok(())
+    }
+}
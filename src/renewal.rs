@@ -0,0 +1,147 @@
+//! Background renewal of tokens before they expire.
+
+use core::future::Future;
+
+use n0_future::time::{Duration, SystemTime};
+
+use crate::{Expires, Rcan, RcanStore};
+
+/// Keeps a set of tokens fresh by renewing each one shortly before it
+/// expires.
+///
+/// `renew` is called with a token that expires within `renew_before` and
+/// returns its replacement, e.g. by asking the issuer for a new one. The
+/// replacement takes the old token's place in the [`RcanStore`]. Its error
+/// type `E` is passed through as is.
+///
+/// Drive it with [`RenewalManager::run`] in a background task, or call
+/// [`RenewalManager::renew_due`] from an existing timer.
+pub struct RenewalManager<C, R> {
+    store: RcanStore<C>,
+    renew_before: Duration,
+    renew: R,
+}
+
+impl<C, R, F, E> RenewalManager<C, R>
+where
+    R: FnMut(&Rcan<C>) -> F,
+    F: Future<Output = Result<Rcan<C>, E>>,
+{
+    /// Creates a manager renewing tokens `renew_before` they expire.
+    pub fn new(renew_before: Duration, renew: R) -> Self {
+        Self {
+            store: RcanStore::new(),
+            renew_before,
+            renew,
+        }
+    }
+
+    /// Adds a token to keep fresh.
+    pub fn insert(&mut self, token: Rcan<C>) {
+        self.store.insert(token);
+    }
+
+    /// The current tokens, with renewals applied.
+    pub fn store(&self) -> &RcanStore<C> {
+        &self.store
+    }
+
+    /// When the next token is due for renewal, or `None` if no token
+    /// expires on the wall clock.
+    pub fn next_due(&self) -> Option<SystemTime> {
+        self.store
+            .tokens()
+            .iter()
+            .filter_map(|token| match token.expires() {
+                Expires::Never | Expires::AtBlock(_) => None,
+                Expires::At(secs) => Some(*secs),
+            })
+            .min()
+            .map(|secs| {
+                let expiry = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+                expiry
+                    .checked_sub(self.renew_before)
+                    .unwrap_or(SystemTime::UNIX_EPOCH)
+            })
+    }
+
+    /// Renews every token that expires within the renewal threshold of
+    /// `now`, returning how many were renewed.
+    ///
+    /// Stops at the first failed renewal, keeping that token as it is.
+    pub async fn renew_due(&mut self, now: SystemTime) -> Result<usize, E> {
+        let mut renewed = 0;
+        for token in self.store.tokens_mut() {
+            if token.expires().expires_within(self.renew_before, now) {
+                *token = (self.renew)(token).await?;
+                renewed += 1;
+            }
+        }
+        Ok(renewed)
+    }
+
+    /// Renews tokens as they come due, forever.
+    ///
+    /// Sleeps with [`n0_future::time::sleep`] in between, so this needs a
+    /// runtime providing timers. Returns the first failed renewal. A
+    /// renewal that is itself due right away is retried after a second,
+    /// rather than in a busy loop.
+    pub async fn run(&mut self) -> Result<(), E> {
+        loop {
+            let now = SystemTime::now();
+            let wait = match self.next_due() {
+                Some(due) => due.duration_since(now).unwrap_or_default(),
+                // Nothing expires, but tokens may be inserted later.
+                None => self.renew_before,
+            };
+            n0_future::time::sleep(wait.max(Duration::from_secs(1))).await;
+            self.renew_due(SystemTime::now()).await?;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use std::{cell::Cell, convert::Infallible};
+
+    use ed25519_dalek::SigningKey;
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::test::Rpc;
+
+    #[test]
+    fn test_renewal_once() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let expires_at = |time: SystemTime| Expires::At(crate::unix_secs(time));
+
+        let renewals = Cell::new(0);
+        let mut manager = RenewalManager::new(Duration::from_secs(60), |token: &Rcan<Rpc>| {
+            renewals.set(renewals.get() + 1);
+            let renewed = token.to_builder(&issuer).sign(expires_at(at(3600)));
+            async move { Ok::<_, Infallible>(renewed) }
+        });
+        manager
+            .insert(Rcan::issuing_builder(&issuer, audience, Rpc::Read).sign(expires_at(at(300))));
+        manager.insert(Rcan::issuing_builder(&issuer, audience, Rpc::All).sign(Expires::Never));
+        assert_eq!(manager.next_due(), Some(at(240)));
+
+        n0_future::future::block_on(async {
+            // Not yet within the threshold.
+            assert_eq!(manager.renew_due(at(200)).await?, 0);
+            // Nearing expiry, renewed once and not again.
+            assert_eq!(manager.renew_due(at(250)).await?, 1);
+            assert_eq!(manager.renew_due(at(260)).await?, 0);
+            Ok::<_, Infallible>(())
+        })?;
+
+        assert_eq!(renewals.get(), 1);
+        assert_eq!(manager.store().tokens()[0].expires(), &expires_at(at(3600)));
+        assert_eq!(manager.next_due(), Some(at(3540)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+//! Signature schemes tokens are signed with.
+//!
+//! [`Rcan`], its [`Payload`], [`RcanBuilder`] and the [`Authorizer`] are
+//! generic over a [`SignatureScheme`], and default to [`Ed25519`]. Tokens
+//! of other schemes, e.g. secp256k1 for interop with ledgers, encode like
+//! ed25519 ones with the scheme's key and signature bytes in their place.
+//! The extras around tokens, such as [`ProofChain`]s, stores and the
+//! alternative encodings, are ed25519 only.
+//!
+//! [`Rcan`]: crate::Rcan
+//! [`Payload`]: crate::Payload
+//! [`RcanBuilder`]: crate::RcanBuilder
+//! [`Authorizer`]: crate::Authorizer
+//! [`ProofChain`]: crate::ProofChain
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use ed25519_dalek::ed25519::signature::Signer;
+
+use crate::{Issuer, Result};
+
+/// A signature scheme: its key and signature types, how to sign and
+/// verify with them, and how they encode.
+///
+/// Tokens, their builders and authorizers default to [`Ed25519`], see the
+/// [`Authorizer`](crate::Authorizer) for the checks other schemes get.
+pub trait SignatureScheme: Copy + Debug + Eq {
+    /// The public key identifying issuers and audiences.
+    type VerifyingKey: Copy + Debug + Eq;
+    /// The secret key tokens are signed with, an [`Issuer`] of the scheme
+    /// so builders created with it sign with the scheme.
+    type SigningKey: Issuer<Self>;
+    /// A signature over a token's preimage.
+    type Signature: Copy + Debug + Eq;
+
+    /// The length of an encoded signature, which is written without a
+    /// length prefix.
+    const SIGNATURE_LENGTH: usize;
+
+    /// The public key of `key`.
+    fn verifying_key(key: &Self::SigningKey) -> Self::VerifyingKey;
+
+    /// Signs `msg` with `key`.
+    fn sign(key: &Self::SigningKey, msg: &[u8]) -> Self::Signature;
+
+    /// Verifies that `signature` is a signature of `msg` by `key`.
+    fn verify(key: &Self::VerifyingKey, msg: &[u8], signature: &Self::Signature) -> Result<()>;
+
+    /// The encoding of `key`.
+    fn key_to_bytes(key: &Self::VerifyingKey) -> Vec<u8>;
+
+    /// Decodes a key encoded with [`SignatureScheme::key_to_bytes`].
+    fn key_from_bytes(bytes: &[u8]) -> Result<Self::VerifyingKey>;
+
+    /// The encoding of `signature`, [`SignatureScheme::SIGNATURE_LENGTH`]
+    /// bytes long.
+    fn signature_to_bytes(signature: &Self::Signature) -> Vec<u8>;
+
+    /// Decodes a signature encoded with
+    /// [`SignatureScheme::signature_to_bytes`].
+    fn signature_from_bytes(bytes: &[u8]) -> Result<Self::Signature>;
+}
+
+/// Ed25519 signatures, as implemented by `ed25519-dalek`.
+///
+/// Verification is strict, rejecting weak keys and malleable signatures.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ed25519;
+
+impl SignatureScheme for Ed25519 {
+    type VerifyingKey = ed25519_dalek::VerifyingKey;
+    type SigningKey = ed25519_dalek::SigningKey;
+    type Signature = ed25519_dalek::Signature;
+
+    const SIGNATURE_LENGTH: usize = ed25519_dalek::SIGNATURE_LENGTH;
+
+    fn verifying_key(key: &Self::SigningKey) -> Self::VerifyingKey {
+        key.verifying_key()
+    }
+
+    fn sign(key: &Self::SigningKey, msg: &[u8]) -> Self::Signature {
+        Signer::sign(key, msg)
+    }
+
+    fn verify(key: &Self::VerifyingKey, msg: &[u8], signature: &Self::Signature) -> Result<()> {
+        key.verify_strict(msg, signature)?;
+        Ok(())
+    }
+
+    fn key_to_bytes(key: &Self::VerifyingKey) -> Vec<u8> {
+        key.as_bytes().to_vec()
+    }
+
+    fn key_from_bytes(bytes: &[u8]) -> Result<Self::VerifyingKey> {
+        Ok(Self::VerifyingKey::try_from(bytes)?)
+    }
+
+    fn signature_to_bytes(signature: &Self::Signature) -> Vec<u8> {
+        signature.to_vec()
+    }
+
+    fn signature_from_bytes(bytes: &[u8]) -> Result<Self::Signature> {
+        Ok(Self::Signature::from_slice(bytes)?)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::{test::Rpc, Authorizer, Expires, Rcan, RcanError, DST};
+
+    /// Ed25519 over prefixed messages, a scheme whose signatures don't
+    /// verify as [`Ed25519`] ones.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Prefixed;
+
+    struct PrefixedKey(ed25519_dalek::SigningKey);
+
+    impl Issuer<Prefixed> for PrefixedKey {
+        fn verifying_key(&self) -> ed25519_dalek::VerifyingKey {
+            self.0.verifying_key()
+        }
+    }
+
+    fn prefixed(msg: &[u8]) -> Vec<u8> {
+        [b"prefixed".as_slice(), msg].concat()
+    }
+
+    impl SignatureScheme for Prefixed {
+        type VerifyingKey = ed25519_dalek::VerifyingKey;
+        type SigningKey = PrefixedKey;
+        type Signature = ed25519_dalek::Signature;
+
+        const SIGNATURE_LENGTH: usize = Ed25519::SIGNATURE_LENGTH;
+
+        fn verifying_key(key: &Self::SigningKey) -> Self::VerifyingKey {
+            key.0.verifying_key()
+        }
+
+        fn sign(key: &Self::SigningKey, msg: &[u8]) -> Self::Signature {
+            Ed25519::sign(&key.0, &prefixed(msg))
+        }
+
+        fn verify(key: &Self::VerifyingKey, msg: &[u8], signature: &Self::Signature) -> Result<()> {
+            Ed25519::verify(key, &prefixed(msg), signature)
+        }
+
+        fn key_to_bytes(key: &Self::VerifyingKey) -> Vec<u8> {
+            Ed25519::key_to_bytes(key)
+        }
+
+        fn key_from_bytes(bytes: &[u8]) -> Result<Self::VerifyingKey> {
+            Ed25519::key_from_bytes(bytes)
+        }
+
+        fn signature_to_bytes(signature: &Self::Signature) -> Vec<u8> {
+            Ed25519::signature_to_bytes(signature)
+        }
+
+        fn signature_from_bytes(bytes: &[u8]) -> Result<Self::Signature> {
+            Ed25519::signature_from_bytes(bytes)
+        }
+    }
+
+    /// Signs and verifies through the trait only.
+    fn roundtrip<S: SignatureScheme>(key: &S::SigningKey, msg: &[u8]) -> Result<()> {
+        let signature = S::sign(key, msg);
+        S::verify(&S::verifying_key(key), msg, &signature)
+    }
+
+    #[test]
+    fn test_ed25519_scheme() -> TestResult {
+        let service = ed25519_dalek::SigningKey::from_bytes(&[0u8; 32]);
+        let alice = ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]);
+        roundtrip::<Ed25519>(&service, b"hello")?;
+
+        // Tokens are signed with the scheme.
+        let rcan =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::Read).sign(Expires::Never);
+        let preimage = postcard::to_extend(&rcan.payload, DST.to_vec())?;
+        let signature = Ed25519::sign(&service, &preimage);
+        assert_eq!(signature, rcan.signature);
+        Ed25519::verify(&service.verifying_key(), &preimage, &signature)?;
+
+        let err = Ed25519::verify(&alice.verifying_key(), &preimage, &signature).unwrap_err();
+        assert!(matches!(err, RcanError::InvalidSignature(_)), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generic_scheme() -> TestResult {
+        let service = PrefixedKey(ed25519_dalek::SigningKey::from_bytes(&[0u8; 32]));
+        let alice = PrefixedKey(ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]));
+        let bob = ed25519_dalek::SigningKey::from_bytes(&[2u8; 32]);
+        let owner = service.0.verifying_key();
+
+        // Builders sign with the scheme of their issuer.
+        let root = Rcan::issuing_builder(&service, alice.0.verifying_key(), Rpc::ReadWrite)
+            .sign(Expires::Never);
+        let delegation = Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Rpc::Read)
+            .sign_attenuated(&root, Expires::Never)?;
+        let preimage = postcard::to_extend(&root.payload, DST.to_vec())?;
+        Prefixed::verify(&owner, &preimage, &root.signature)?;
+
+        // Tokens encode like ed25519 ones, but only verify with their scheme.
+        let encoded = root.encode();
+        assert_eq!(Rcan::<Rpc, Prefixed>::decode(&encoded)?, root);
+        assert!(Rcan::<Rpc>::decode(&encoded).is_err());
+        let ed25519 = Rcan::issuing_builder(&service.0, alice.0.verifying_key(), Rpc::ReadWrite)
+            .sign(Expires::Never);
+        assert_eq!(ed25519.encode().len(), encoded.len());
+        assert!(Rcan::<Rpc, Prefixed>::decode(&ed25519.encode()).is_err());
+
+        let authorizer = Authorizer::<Prefixed>::new(owner);
+        let _ = authorizer.check_invocation_from(
+            bob.verifying_key(),
+            Rpc::Read,
+            &[&root, &delegation],
+        )?;
+        let err = authorizer
+            .check_invocation_from(bob.verifying_key(), Rpc::ReadWrite, &[&root, &delegation])
+            .unwrap_err();
+        assert!(
+            matches!(err, RcanError::CapabilityNotPermitted { proof: 1 }),
+            "{err}"
+        );
+
+        Ok(())
+    }
+}
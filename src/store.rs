@@ -0,0 +1,247 @@
+//! A pool of tokens to search for proof chains.
+
+use alloc::{vec, vec::Vec};
+
+use ed25519_dalek::VerifyingKey;
+
+use crate::{attenuate, Authorizer, Capability, CapabilityOrigin, Position, Rcan};
+
+/// A collection of tokens, e.g. every delegation a service has been
+/// presented with, from which proof chains can be assembled.
+///
+/// Tokens are kept verified, as decoded, in insertion order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RcanStore<C> {
+    tokens: Vec<Rcan<C>>,
+}
+
+impl<C> Default for RcanStore<C> {
+    fn default() -> Self {
+        Self { tokens: Vec::new() }
+    }
+}
+
+impl<C> RcanStore<C> {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a token to the store.
+    pub fn insert(&mut self, token: Rcan<C>) {
+        self.tokens.push(token);
+    }
+
+    /// The tokens in the store, in insertion order.
+    pub fn tokens(&self) -> &[Rcan<C>] {
+        &self.tokens
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn tokens_mut(&mut self) -> &mut [Rcan<C>] {
+        &mut self.tokens
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Every token that may stop working if `key` is revoked, in insertion
+    /// order, to review before revoking it.
+    ///
+    /// These are the tokens `key` issued, is the audience of or is the
+    /// delegation root of, and the delegations following them, found by
+    /// [`Rcan::links_to`]. A following delegation may still be valid
+    /// through another chain that doesn't involve `key`.
+    pub fn dependents_of(&self, key: &VerifyingKey) -> Vec<&Rcan<C>>
+    where
+        C: Capability,
+    {
+        let mut dependent: Vec<bool> = self
+            .tokens
+            .iter()
+            .map(|token| {
+                token.issuer_is(key)
+                    || token.audience_is(key)
+                    || token.capability_origin() == &CapabilityOrigin::Delegation(*key)
+            })
+            .collect();
+        // Propagate down the chains until no more tokens are added.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, token) in self.tokens.iter().enumerate() {
+                if !dependent[i]
+                    && self
+                        .tokens
+                        .iter()
+                        .zip(&dependent)
+                        .any(|(previous, dependent)| *dependent && previous.links_to(token))
+                {
+                    dependent[i] = true;
+                    changed = true;
+                }
+            }
+        }
+        self.tokens
+            .iter()
+            .zip(dependent)
+            .filter_map(|(token, dependent)| dependent.then_some(token))
+            .collect()
+    }
+
+    /// Everything `invoker` can do with `owner`'s capabilities, through any
+    /// chain of stored tokens that is valid at `now`.
+    ///
+    /// Returns the capabilities granted to `invoker` at the end of each
+    /// valid chain, attenuated by the proofs before it with
+    /// [`Capability::meet`], so the same capability may show up more than
+    /// once. Chains are found by following [`Rcan::links_to`] from
+    /// every token `owner` issued, and are checked like in
+    /// [`Authorizer::check_invocation_from`].
+    pub fn effective_capabilities_for(
+        &self,
+        owner: VerifyingKey,
+        invoker: VerifyingKey,
+        now: impl Into<Position>,
+    ) -> Vec<C>
+    where
+        C: Capability + Clone,
+    {
+        let now = now.into();
+        let authorizer = Authorizer::new(owner);
+        let mut capabilities = Vec::new();
+        for root in &self.tokens {
+            if root.issuer_is(&owner) && root.capability_origin() == &CapabilityOrigin::Issuer {
+                self.collect_chains(
+                    &authorizer,
+                    invoker,
+                    now,
+                    &mut vec![root],
+                    &mut capabilities,
+                );
+            }
+        }
+        capabilities
+    }
+
+    /// Depth-first search for valid chains extending `chain`.
+    fn collect_chains<'a>(
+        &'a self,
+        authorizer: &Authorizer,
+        invoker: VerifyingKey,
+        now: Position,
+        chain: &mut Vec<&'a Rcan<C>>,
+        capabilities: &mut Vec<C>,
+    ) where
+        C: Capability + Clone,
+    {
+        let last = *chain.last().expect("chains start at a root");
+        if authorizer
+            .check_chain_integrity(now, invoker, chain)
+            .is_ok()
+        {
+            let (_, proofs) = chain.split_last().expect("chains start at a root");
+            capabilities.extend(
+                last.capabilities()
+                    .iter()
+                    .filter_map(|capability| attenuate(capability, proofs)),
+            );
+        }
+
+        for next in &self.tokens {
+            // Skip tokens already in the chain, so cycles terminate.
+            if last.links_to(next) && !chain.iter().any(|proof| core::ptr::eq(*proof, next)) {
+                chain.push(next);
+                self.collect_chains(authorizer, invoker, now, chain, capabilities);
+                chain.pop();
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use ed25519_dalek::SigningKey;
+
+    use super::*;
+    use crate::{test::Rpc, Expires, SystemTime};
+
+    #[test]
+    fn test_effective_capabilities_for() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let owner = service.verifying_key();
+
+        let mut store = RcanStore::new();
+        // service -> bob: Read
+        store.insert(
+            Rcan::issuing_builder(&service, bob.verifying_key(), Rpc::Read).sign(Expires::Never),
+        );
+        // service -> alice -> bob: ReadWrite
+        store.insert(
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All).sign(Expires::Never),
+        );
+        store.insert(
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Rpc::ReadWrite)
+                .sign(Expires::Never),
+        );
+        // expired, and for someone else
+        store.insert(
+            Rcan::issuing_builder(&service, bob.verifying_key(), Rpc::All).sign(Expires::At(0)),
+        );
+        store.insert(
+            Rcan::issuing_builder(&service, carol.verifying_key(), Rpc::All).sign(Expires::Never),
+        );
+
+        let capabilities =
+            store.effective_capabilities_for(owner, bob.verifying_key(), SystemTime::now());
+        assert_eq!(capabilities, vec![Rpc::Read, Rpc::ReadWrite]);
+
+        assert_eq!(
+            store.effective_capabilities_for(owner, alice.verifying_key(), SystemTime::now()),
+            vec![Rpc::All]
+        );
+    }
+
+    #[test]
+    fn test_dependents_of() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+        let owner = service.verifying_key();
+
+        let service_alice =
+            Rcan::issuing_builder(&service, alice.verifying_key(), Rpc::All).sign(Expires::Never);
+        let alice_bob =
+            Rcan::delegating_builder(&alice, bob.verifying_key(), owner, Rpc::ReadWrite)
+                .sign(Expires::Never);
+        let bob_carol = Rcan::delegating_builder(&bob, carol.verifying_key(), owner, Rpc::Read)
+            .sign(Expires::Never);
+        let service_carol =
+            Rcan::issuing_builder(&service, carol.verifying_key(), Rpc::Read).sign(Expires::Never);
+
+        let mut store = RcanStore::new();
+        for token in [&service_carol, &service_alice, &alice_bob, &bob_carol] {
+            store.insert(token.clone());
+        }
+
+        // Bob's delegation to Carol only works through Alice's.
+        assert_eq!(
+            store.dependents_of(&alice.verifying_key()),
+            vec![&service_alice, &alice_bob, &bob_carol]
+        );
+        assert_eq!(
+            store.dependents_of(&bob.verifying_key()),
+            vec![&alice_bob, &bob_carol]
+        );
+        assert_eq!(store.dependents_of(&owner).len(), 4);
+    }
+}
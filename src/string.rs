@@ -0,0 +1,95 @@
+//! String encodings of [`Rcan`] tokens, for HTTP headers and JSON config.
+//!
+//! Both are over the exact bytes of [`Rcan::encode`], so they decode
+//! through [`Rcan::decode`], signature check included.
+
+use alloc::{format, string::String};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Rcan, RcanError, Result};
+
+/// The multibase prefix of unpadded base64url.
+const MULTIBASE_BASE64URL: char = 'u';
+
+impl<C> Rcan<C> {
+    /// Encodes the token as unpadded base64url.
+    pub fn encode_base64(&self) -> String
+    where
+        C: Serialize,
+    {
+        URL_SAFE_NO_PAD.encode(self.encode())
+    }
+
+    /// Decodes a token from unpadded base64url and verifies its signature.
+    pub fn decode_base64(encoded: &str) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|err| RcanError::Malformed(format!("decoding base64: {err}")))?;
+        Self::decode(&bytes)
+    }
+
+    /// Encodes the token as a [multibase] string, which names its base in
+    /// a one character prefix. Always uses unpadded base64url (`u`).
+    ///
+    /// [multibase]: https://github.com/multiformats/multibase
+    pub fn to_multibase(&self) -> String
+    where
+        C: Serialize,
+    {
+        format!("{MULTIBASE_BASE64URL}{}", self.encode_base64())
+    }
+
+    /// Decodes a token from a multibase string and verifies its signature.
+    ///
+    /// Only unpadded base64url (`u`) is supported.
+    pub fn from_multibase(encoded: &str) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let Some(encoded) = encoded.strip_prefix(MULTIBASE_BASE64URL) else {
+            return Err(RcanError::Malformed(format!(
+                "unsupported multibase prefix: {:?}",
+                encoded.chars().next()
+            )));
+        };
+        Self::decode_base64(encoded)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use ed25519_dalek::SigningKey;
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::{test::Rpc, Expires};
+
+    #[test]
+    fn test_base64_roundtrip() -> TestResult {
+        let issuer = SigningKey::from_bytes(&[0u8; 32]);
+        let audience = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let rcan = Rcan::issuing_builder(&issuer, audience, Rpc::Read).sign(Expires::Never);
+
+        let encoded = rcan.encode_base64();
+        assert!(!encoded.contains(['=', '+', '/']));
+        assert_eq!(Rcan::<Rpc>::decode_base64(&encoded)?, rcan);
+
+        let multibase = rcan.to_multibase();
+        assert_eq!(multibase.strip_prefix('u'), Some(encoded.as_str()));
+        assert_eq!(Rcan::<Rpc>::from_multibase(&multibase)?, rcan);
+        assert!(Rcan::<Rpc>::from_multibase(&encoded).is_err());
+
+        // The signature is still checked.
+        let mut forged = rcan.encode();
+        let n = forged.len();
+        forged[n - 1] ^= 1;
+        assert!(Rcan::<Rpc>::decode_base64(&URL_SAFE_NO_PAD.encode(forged)).is_err());
+
+        Ok(())
+    }
+}
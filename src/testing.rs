@@ -0,0 +1,41 @@
+//! Support for [`test_roundtrip!`](crate::test_roundtrip).
+
+use core::fmt::Debug;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::Rcan;
+
+/// Asserts that a token survives an encoding round trip.
+///
+/// Meant for downstream tests guarding their own capability encodings:
+/// given a sample token, checks that decoding its encoding yields the same
+/// token, that its signature verifies, and that re-encoding the decoded
+/// token is byte-for-byte stable.
+///
+/// ```ignore
+/// rcan::test_roundtrip!(sample_token);
+/// ```
+#[macro_export]
+macro_rules! test_roundtrip {
+    ($token:expr) => {
+        $crate::testing::assert_roundtrip(&$token)
+    };
+}
+
+#[track_caller]
+pub fn assert_roundtrip<C>(token: &Rcan<C>)
+where
+    C: DeserializeOwned + Serialize + PartialEq + Debug,
+{
+    if let Err(err) = token.verify_self() {
+        panic!("token doesn't verify: {err:#}");
+    }
+    let encoded = token.encode();
+    let decoded = match Rcan::<C>::decode(&encoded) {
+        Ok(decoded) => decoded,
+        Err(err) => panic!("encoded token doesn't decode: {err:#}"),
+    };
+    assert_eq!(&decoded, token, "token changed in a round trip");
+    assert_eq!(decoded.encode(), encoded, "encoding isn't stable");
+}
@@ -0,0 +1,144 @@
+//! Decoding of tokens whose capabilities hold secrets.
+
+use serde::{de::DeserializeOwned, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    BoxError, Capability, Ed25519, Rcan, RcanError, Result, SignatureScheme, UnverifiedRcan,
+    Witness, VERSION,
+};
+
+/// A capability that is zeroized on drop, as returned by
+/// [`Rcan::decode_zeroizing`].
+impl<C: Capability + Zeroize> Capability for Zeroizing<C> {
+    fn permits(&self, other: &Self) -> bool {
+        C::permits(self, other)
+    }
+
+    fn try_permits(&self, other: &Self) -> Result<bool, BoxError> {
+        C::try_permits(self, other)
+    }
+
+    fn permits_with_witness(&self, other: &Self) -> Option<Witness> {
+        C::permits_with_witness(self, other)
+    }
+
+    fn may_delegate(&self) -> bool {
+        C::may_delegate(self)
+    }
+}
+
+impl<C: Zeroize> Rcan<Zeroizing<C>> {
+    /// Decodes a token like [`Rcan::decode`], for capabilities that embed
+    /// secrets, such as other tokens.
+    ///
+    /// The capabilities are zeroized when the token is dropped, and so is
+    /// the signing preimage built to verify the signature. The caller owns
+    /// `bytes`, wrap them in [`Zeroizing`] as well to clear them after use.
+    pub fn decode_zeroizing(bytes: &[u8]) -> Result<Self>
+    where
+        C: DeserializeOwned + Serialize,
+    {
+        let Some((version, rest)) = bytes.split_first() else {
+            return Err(RcanError::EmptyToken);
+        };
+        if *version != VERSION {
+            return Err(RcanError::UnsupportedVersion(*version));
+        }
+        let UnverifiedRcan(rcan) = postcard::from_bytes(rest)?;
+
+        let signed = crate::signing_preimage(&rcan.payload, None);
+        Ed25519::verify(&rcan.payload.issuer, &signed, &rcan.signature)?;
+        Ok(rcan)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use ed25519_dalek::SigningKey;
+    use serde::Deserialize;
+    use testresult::TestResult;
+
+    use super::*;
+    use crate::{Authorizer, Expires, DST};
+
+    /// A capability embedding a bearer secret.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    struct SecretCapability(Vec<u8>);
+
+    impl Capability for SecretCapability {
+        fn permits(&self, other: &Self) -> bool {
+            self == other
+        }
+    }
+
+    impl Zeroize for SecretCapability {
+        fn zeroize(&mut self) {
+            self.0.zeroize();
+        }
+    }
+
+    #[test]
+    fn test_decode_zeroizing() -> TestResult {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let secret = SecretCapability(b"hunter2".to_vec());
+        let rcan = Rcan::issuing_builder(&service, alice.verifying_key(), secret.clone())
+            .sign(Expires::Never);
+        let encoded = Zeroizing::new(rcan.encode());
+
+        let decoded = Rcan::<Zeroizing<SecretCapability>>::decode_zeroizing(&encoded)?;
+        assert_eq!(**decoded.capability(), secret);
+        assert_eq!(decoded.encode(), *encoded);
+        let _ = Authorizer::new(service.verifying_key()).check_invocation_from(
+            alice.verifying_key(),
+            Zeroizing::new(secret.clone()),
+            &[&decoded],
+        )?;
+
+        // The preimage is the zeroized one `signing_preimage` builds.
+        let signed = crate::signing_preimage(&decoded.payload, None);
+        assert_eq!(*signed, decoded.payload.signing_bytes());
+        let mut capability = decoded.into_capability();
+        capability.zeroize();
+        assert!(capability.0.is_empty());
+
+        let mut forged = encoded.to_vec();
+        let n = forged.len();
+        forged[n - 1] ^= 1;
+        assert!(Rcan::<Zeroizing<SecretCapability>>::decode_zeroizing(&forged).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signing_preimage_zeroizing() {
+        let service = SigningKey::from_bytes(&[0u8; 32]);
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let rcan =
+            Rcan::issuing_builder(&service, alice.verifying_key(), SecretCapability(vec![7]))
+                .with_meta("ticket", "42")
+                .sign(Expires::Never);
+
+        // Written into a buffer of its final size, which never moves, so
+        // zeroizing it clears the only copy.
+        for aad in [None, Some(b"aad".as_slice())] {
+            let len = crate::preimage_len(&rcan.payload, aad);
+            let buffer = Vec::with_capacity(len);
+            let start = buffer.as_ptr();
+            let buffer = crate::write_preimage(buffer, &rcan.payload, aad);
+            assert_eq!(buffer.as_ptr(), start);
+            assert!(buffer.len() == len && buffer.capacity() >= len);
+        }
+
+        let preimage: Zeroizing<Vec<u8>> = crate::signing_preimage(&rcan.payload, None);
+        let expected = postcard::to_extend(&rcan.payload, DST.to_vec()).expect("vec");
+        assert_eq!(*preimage, expected);
+
+        let preimage = crate::signing_preimage(&rcan.payload, Some(b"aad"));
+        assert_eq!(
+            *preimage,
+            postcard::to_extend(b"aad".as_slice(), expected).expect("vec")
+        );
+    }
+}